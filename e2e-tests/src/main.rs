@@ -1,4 +1,4 @@
-//mod programs;
+mod programs;
 mod tests;
 mod tools;
 
@@ -9,22 +9,30 @@ use crate::tests::display::test_draw_red_circle;
 use crate::tests::display::test_draw_red_reactangle_with_texture;
 use crate::tests::display::test_draw_triangle_with_texture_only_vertices;
 use crate::tests::display::test_draw_two_triangles;
+use crate::tests::programs::test_programs_start;
 use crate::tests::shaders::test_shader_program_compilation;
+use crate::tests::shaders::test_shader_program_uniforms;
 use rusty_opengl::config::Glfw;
 use rusty_opengl::config::Resolution;
+use std::env;
 use std::rc::Rc;
 
 fn main() {
-    let mut glfw: Glfw = Glfw::default();
+    let mut glfw: Glfw = if env::args().any(|arg| arg == "--invisible") {
+        Glfw::invisible().unwrap()
+    } else {
+        Glfw::default()
+    };
     let resolution = Rc::new(Resolution {
         width: 900,
         height: 600,
     });
-    let (mut window, _events) = glfw.create_window(resolution, "learn opengl");
+    let (mut window, mut events) = glfw.create_window(resolution, "learn opengl").unwrap();
     window.set_current();
     window.load_opengl_func_ptr();
 
     e2e_test!(test_shader_program_compilation);
+    e2e_test!(test_shader_program_uniforms);
     e2e_test!(test_draw_two_triangles &mut glfw, &mut window);
     e2e_test!(test_draw_reactangle &mut glfw, &mut window);
     e2e_test!(test_draw_reactangle_with_texture &mut glfw, &mut window);
@@ -32,6 +40,7 @@ fn main() {
     e2e_test!(test_draw_triangle_with_texture_only_vertices &mut glfw, &mut window);
     e2e_test!(test_draw_red_circle &mut glfw, &mut window);
     e2e_test!(test_draw_green_circle_adjusted &mut glfw, &mut window);
+    e2e_test!(test_programs_start &mut glfw, &mut window, &mut events);
 
     //process_events(&mut window, &events);
 }
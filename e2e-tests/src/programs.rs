@@ -1,49 +1,150 @@
-use crate::tools::utilities::create_shader_program;
-use rusty_opengl::config::Window;
+use rusty_opengl::app::{App, Runner};
+use rusty_opengl::circle::Circle;
+use rusty_opengl::color::RGBA;
 use rusty_opengl::config::{set_background_color, Glfw};
-use rusty_opengl::entities::object::Object;
-use rusty_opengl::polygons::triangle::Triangle;
-use rusty_opengl::polygons::triangle::Vertices;
-use rusty_opengl::shaders::shader_program::Color;
+use rusty_opengl::config::{Window, WindowEvents};
+use rusty_opengl::error::Error;
+use rusty_opengl::math::transform::Transform;
+use rusty_opengl::math::vector::Vec2;
+use rusty_opengl::polygons::databuffer::BufferUsage;
+use rusty_opengl::polygons::vertices::Vertices;
+use rusty_opengl::polygons::{Polygon, PolygonBuilder};
+use rusty_opengl::post_process::effect::Effect;
+use rusty_opengl::post_process::PostProcess;
+use rusty_opengl::render_target::RenderTargetBuilder;
 
 pub fn moving_triangle(glfw: &mut Glfw, window: &mut Window) {
-    let shader_program = create_shader_program("simplest.vert", "uniform.frag");
-    let vertices = Vertices::new([-0.1, -0.3, 0.5, -0.5, 0.0, 0.2]);
-    let triangle = Triangle::new(vertices);
-    let mut red_triangle = Object::new(Box::new(triangle), Some(shader_program), None);
-    red_triangle.init();
-
-    red_triangle.set_uniform_var(
-        "ourColor",
-        &Color {
-            r: 1.0,
-            g: 0.0,
-            b: 0.0,
-            a: 1.0,
-        },
-    );
-
-    let mut vertices_in_move = vec![-0.3, -0.3, 0.5, -0.5, 0.0, 0.0];
+    let mut pol_builder = PolygonBuilder::new();
+    pol_builder.set_vertices(Vertices::new(
+        vec![-0.3, -0.3, 0.0, 0.5, -0.5, 0.0, 0.0, 0.0, 0.0],
+        vec![],
+        vec![],
+    ));
+    pol_builder.set_color(RGBA::from_hex(0xFF_00_00_FF));
+    pol_builder.set_usage(BufferUsage::Stream);
+    let mut red_triangle = pol_builder.build().unwrap();
+
+    let mut first_vertex = [-0.3, -0.3, 0.0];
     let mut is_rising = false;
     while window.is_running_window() {
         set_background_color(0.2, 0.4, 0.6);
-        red_triangle.polygon.set_vertices(&vertices_in_move);
-        red_triangle.polygon.recalculate();
-        red_triangle.render();
+        red_triangle.update_positions(0, &first_vertex).unwrap();
+        red_triangle.draw();
 
         window.swap_buffers();
         glfw.poll_events();
 
         if is_rising {
-            vertices_in_move[0] += 0.01;
+            first_vertex[0] += 0.01;
         } else {
-            vertices_in_move[0] -= 0.01;
+            first_vertex[0] -= 0.01;
         }
 
-        if vertices_in_move[0] > 0.99 {
+        if first_vertex[0] > 0.99 {
             is_rising = false;
-        } else if vertices_in_move[0] < -0.99 {
+        } else if first_vertex[0] < -0.99 {
             is_rising = true;
         }
     }
 }
+
+struct RotatingSquare {
+    square: Option<Polygon>,
+    angle: f32,
+}
+
+impl App for RotatingSquare {
+    fn init(&mut self, _window: &mut Window) -> Result<(), Error> {
+        let mut pol_builder = PolygonBuilder::new();
+        pol_builder.set_vertices(Vertices::new(
+            vec![
+                0.2, 0.2, 0.0, 0.2, -0.2, 0.0, -0.2, -0.2, 0.0, -0.2, 0.2, 0.0,
+            ],
+            vec![],
+            vec![],
+        ));
+        pol_builder.set_color(RGBA::from_hex(0x00_FF_00_FF));
+        pol_builder.set_transform(Transform::from_translation(Vec2::new(0.5, 0.0)));
+        self.square = Some(pol_builder.build()?);
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, dt: f32) {
+        self.angle += dt;
+    }
+
+    fn render(&mut self, _interpolation: f32) {
+        set_background_color(0.2, 0.4, 0.6);
+        if let Some(square) = self.square.as_mut() {
+            square.get_transform_mut().set_rotation(self.angle);
+            square.draw();
+        }
+    }
+}
+
+pub fn rotating_square(glfw: &mut Glfw, window: &mut Window, events: &mut WindowEvents) {
+    let mut app = RotatingSquare {
+        square: None,
+        angle: 0.0,
+    };
+    let mut runner = Runner::new();
+    runner.set_frame_cap(Some(60));
+    runner.run(&mut app, glfw, window, events).unwrap();
+}
+
+pub fn render_to_texture(glfw: &mut Glfw, window: &mut Window) {
+    let target = RenderTargetBuilder::new(256, 256).build().unwrap();
+
+    let mut scene = Circle::new((0.0, 0.0), 0.6, RGBA::from_hex(0xFF_00_00_FF), None);
+    scene.init().unwrap();
+
+    let mut pol_builder = PolygonBuilder::new();
+    pol_builder.set_vertices(Vertices::new(
+        vec![0.9, 0.9, 0.0, 0.9, 0.4, 0.0, 0.4, 0.4, 0.0, 0.4, 0.9, 0.0],
+        vec![],
+        vec![],
+    ));
+    pol_builder.set_texture(target.get_texture());
+    let minimap = pol_builder.build().unwrap();
+
+    while window.is_running_window() {
+        target.bind();
+        set_background_color(1.0, 1.0, 1.0);
+        scene.draw();
+        target.unbind();
+
+        set_background_color(0.2, 0.4, 0.6);
+        scene.draw();
+        minimap.draw();
+
+        window.swap_buffers();
+        glfw.poll_events();
+    }
+}
+
+pub fn post_processed_circle(glfw: &mut Glfw, window: &mut Window) {
+    let resolution = window.get_resolution();
+    let mut post_process = PostProcess::new(resolution.width, resolution.height, 4).unwrap();
+    post_process.push_effect(Effect::bloom(0.5, 1.5).unwrap());
+    post_process.push_effect(Effect::vignette(0.4, 0.3).unwrap());
+    post_process.push_effect(Effect::fxaa().unwrap());
+
+    let mut scene = Circle::new((0.0, 0.0), 0.6, RGBA::from_hex(0xFF_CC_00_FF), None);
+    scene.init().unwrap();
+
+    while window.is_running_window() {
+        window.sync_framebuffer_size();
+        let resolution = window.get_resolution();
+        post_process
+            .resize(resolution.width, resolution.height)
+            .unwrap();
+
+        post_process.begin();
+        set_background_color(0.1, 0.1, 0.2);
+        scene.draw();
+        post_process.end();
+
+        window.swap_buffers();
+        glfw.poll_events();
+    }
+}
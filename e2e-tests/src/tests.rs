@@ -1,2 +1,3 @@
 pub mod display;
+pub mod programs;
 pub mod shaders;
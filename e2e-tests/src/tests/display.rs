@@ -1,4 +1,5 @@
 use crate::tools::images::check_images_equality;
+use rusty_opengl::camera::Camera2D;
 use rusty_opengl::circle::Circle;
 use rusty_opengl::color::RGBA;
 use rusty_opengl::config::{set_background_color, Glfw, Window};
@@ -70,7 +71,7 @@ pub fn test_draw_reactangle_with_texture(glfw: &mut Glfw, window: &mut Window) -
         vec![],
     );
 
-    let mut texture = Texture::new(Path::new("e2e-tests/assets/texture/container.jpg"));
+    let mut texture = Texture::new(Path::new("e2e-tests/assets/texture/container.jpg")).unwrap();
     texture.set_default();
     pol_builder.set_vertices(vertices);
     pol_builder.set_texture(texture);
@@ -95,7 +96,7 @@ pub fn test_draw_red_reactangle_with_texture(glfw: &mut Glfw, window: &mut Windo
         vec![],
     );
 
-    let texture = Texture::new(Path::new("e2e-tests/assets/texture/container.jpg"));
+    let texture = Texture::new(Path::new("e2e-tests/assets/texture/container.jpg")).unwrap();
     texture.set_filtering(Filtering::Nearest);
     texture.set_wrapping(Wrapping::Repeat);
     pol_builder.set_vertices(vertices);
@@ -120,7 +121,7 @@ pub fn test_draw_triangle_with_texture_only_vertices(glfw: &mut Glfw, window: &m
         vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0],
     );
 
-    let texture = Texture::new(Path::new("e2e-tests/assets/texture/container.jpg"));
+    let texture = Texture::new(Path::new("e2e-tests/assets/texture/container.jpg")).unwrap();
     texture.set_filtering(Filtering::Nearest);
     texture.set_wrapping(Wrapping::Repeat);
     pol_builder.set_vertices(vertices);
@@ -138,7 +139,7 @@ pub fn test_draw_triangle_with_texture_only_vertices(glfw: &mut Glfw, window: &m
 
 pub fn test_draw_red_circle(glfw: &mut Glfw, window: &mut Window) -> bool {
     let mut circle = Circle::new((0_f32, 0_f32), 0.3_f32, RGBA::from_hex(0xFF_00_00_FF), None);
-    circle.init();
+    circle.init().unwrap();
 
     set_background_color(0.1, 0.2, 0.2);
 
@@ -151,12 +152,13 @@ pub fn test_draw_red_circle(glfw: &mut Glfw, window: &mut Window) -> bool {
 
 pub fn test_draw_green_circle_adjusted(glfw: &mut Glfw, window: &mut Window) -> bool {
     let mut circle = Circle::new((0_f32, 0_f32), 0.3_f32, RGBA::from_hex(0x00_FF_00_FF), None);
-    circle.adjust_radius(window.get_resolution());
-    circle.init();
+    circle.init().unwrap();
+    let mut camera = Camera2D::normalized((900, 600));
+    camera.follow(window);
 
     set_background_color(0.1, 0.2, 0.2);
 
-    circle.draw();
+    circle.draw_with_camera(&camera);
     window.swap_buffers();
     glfw.poll_events();
 
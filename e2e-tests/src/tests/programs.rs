@@ -0,0 +1,18 @@
+use crate::programs::{moving_triangle, post_processed_circle, render_to_texture, rotating_square};
+use rusty_opengl::config::{Glfw, Window, WindowEvents};
+
+/// Sets up every demo program, they return before their first frame because
+/// the window is marked to close
+pub fn test_programs_start(
+    glfw: &mut Glfw,
+    window: &mut Window,
+    events: &mut WindowEvents,
+) -> bool {
+    window.set_should_close(true);
+    moving_triangle(glfw, window);
+    rotating_square(glfw, window, events);
+    render_to_texture(glfw, window);
+    post_processed_circle(glfw, window);
+    window.set_should_close(false);
+    window.is_running_window()
+}
@@ -12,9 +12,30 @@ pub fn test_shader_program_compilation() -> bool {
     let mut shader_program = ShaderProgram::new(&vertex_src, &fragment_src);
 
     assert!(!shader_program.is_compiled());
-    shader_program.compile();
+    assert!(shader_program.compile().is_ok());
     assert!(shader_program.is_compiled());
     shader_program.activate();
 
     true
 }
+
+pub fn test_shader_program_uniforms() -> bool {
+    let path_vertex_src = get_path_to_shaders() + "simplest.vert";
+    let path_fragment_src = get_path_to_shaders() + "uniform.frag";
+
+    let vertex_src = read_src_from_file(Path::new(&path_vertex_src)).unwrap();
+    let fragment_src = read_src_from_file(Path::new(&path_fragment_src)).unwrap();
+    let mut shader_program = ShaderProgram::new(&vertex_src, &fragment_src);
+
+    assert!(shader_program
+        .set_uniform("ourColor", &[1.0, 0.0, 0.0, 1.0])
+        .is_err());
+    assert!(shader_program.compile().is_ok());
+    assert!(shader_program.get_uniform_location("ourColor").is_some());
+    assert!(shader_program
+        .set_uniform("ourColor", &[1.0, 0.0, 0.0, 1.0])
+        .is_ok());
+    assert!(shader_program.set_uniform("notExisting", &1.0_f32).is_err());
+
+    true
+}
@@ -1,4 +1,3 @@
-import hashlib
 import sys
 from PIL import Image
 import os
@@ -17,26 +16,18 @@ TEMPLATE_IMAGE_PATH = get_path_to_templates() + TEMPLATE_IMAGE_NAME
 GENERATED_IMAGE_PATH = "test_result_" + TEMPLATE_IMAGE_NAME
 
 
-def transform_vertically(path: str):
-    image = Image.open(path)
-    image = image.transpose(Image.Transpose.FLIP_TOP_BOTTOM)
-    image.save(path)
-
-
-def get_hash(path: str):
-    with open(path, "rb") as f:
-        bytes = f.read()
-        return hashlib.sha256(bytes).hexdigest()
+def get_pixels(path: str):
+    with Image.open(path) as image:
+        return image.size, image.convert("RGB").tobytes()
 
 
 def test_images():
-    template_image_hash = get_hash(TEMPLATE_IMAGE_PATH)
-    generated_image_hash = get_hash(GENERATED_IMAGE_PATH)
+    template_pixels = get_pixels(TEMPLATE_IMAGE_PATH)
+    generated_pixels = get_pixels(GENERATED_IMAGE_PATH)
 
-    if template_image_hash != generated_image_hash:
+    if template_pixels != generated_pixels:
         sys.stderr.write(f"Images are not equal! {TEMPLATE_IMAGE_NAME}")
         sys.exit(1)
 
 
-transform_vertically(GENERATED_IMAGE_PATH)
 test_images()
\ No newline at end of file
@@ -0,0 +1,303 @@
+pub mod clock;
+pub mod stats;
+
+use crate::app::clock::{Clock, SystemClock};
+use crate::app::stats::FrameStats;
+use crate::config::{Glfw, Window, WindowEvents};
+use crate::error::Error;
+use crate::events::Event;
+use std::time::Duration;
+
+/// Hooks called by `Runner`, every one of them is optional
+pub trait App {
+    /// Called once before the first frame, with the context already current
+    ///
+    /// # Errors
+    ///
+    /// Returning `Err` stops the runner before the first frame
+    fn init(&mut self, _window: &mut Window) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called for every event before the updates of the frame
+    fn on_event(&mut self, _event: &Event) {}
+
+    /// Called zero or more times per frame with the fixed timestep in seconds
+    fn fixed_update(&mut self, _dt: f32) {}
+
+    /// Called once per frame with the frame time in seconds
+    fn update(&mut self, _dt: f32) {}
+
+    /// `interpolation` in [0, 1) is how far the time is between the last and
+    /// the next fixed update
+    fn render(&mut self, _interpolation: f32) {}
+}
+
+/// Largest `f32` below 1, the upper bound of the interpolation
+const MAX_INTERPOLATION: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/// Drives an `App` with a fixed-timestep accumulator and an optional
+/// frame-rate cap
+pub struct Runner<C: Clock = SystemClock> {
+    clock: C,
+    fixed_timestep: Duration,
+    max_fixed_steps: u32,
+    max_frame_time: Duration,
+    frame_cap: Option<u32>,
+    accumulator: Duration,
+    last_frame: Option<Duration>,
+    stats: FrameStats,
+}
+
+impl Runner<SystemClock> {
+    /// 60 fixed updates per second, no frame cap
+    #[must_use]
+    pub fn new() -> Self {
+        Runner::with_clock(SystemClock::new())
+    }
+}
+
+impl Default for Runner<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> Runner<C> {
+    #[must_use]
+    pub fn with_clock(clock: C) -> Self {
+        Runner {
+            clock,
+            fixed_timestep: Duration::from_secs(1) / 60,
+            max_fixed_steps: 8,
+            max_frame_time: Duration::from_millis(250),
+            frame_cap: None,
+            accumulator: Duration::ZERO,
+            last_frame: None,
+            stats: FrameStats::default(),
+        }
+    }
+
+    /// Zero durations are ignored
+    pub fn set_fixed_timestep(&mut self, timestep: Duration) {
+        if !timestep.is_zero() {
+            self.fixed_timestep = timestep;
+        }
+    }
+
+    /// Upper limit of `fixed_update` calls per frame, the remaining time
+    /// beyond one step is dropped so a slow frame does not cause even slower
+    /// ones
+    pub fn set_max_fixed_steps(&mut self, steps: u32) {
+        self.max_fixed_steps = steps.max(1);
+    }
+
+    /// Longer frames (e.g. after a breakpoint) are clamped to this duration
+    pub fn set_max_frame_time(&mut self, duration: Duration) {
+        self.max_frame_time = duration;
+    }
+
+    /// Maximum frames per second, `None` or 0 runs as fast as possible
+    pub fn set_frame_cap(&mut self, frames_per_second: Option<u32>) {
+        self.frame_cap = frames_per_second.filter(|fps| *fps > 0);
+    }
+
+    #[must_use]
+    pub fn get_stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    #[must_use]
+    pub fn get_clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn get_clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Runs until the window is closed
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `App::init` fails
+    pub fn run(
+        &mut self,
+        app: &mut impl App,
+        glfw: &mut Glfw,
+        window: &mut Window,
+        events: &mut WindowEvents,
+    ) -> Result<(), Error> {
+        app.init(window)?;
+        while window.is_running_window() {
+            glfw.poll_events();
+            let frame_events = window.process_events(events);
+            self.step(app, &frame_events);
+            window.swap_buffers();
+            self.wait_for_next_frame();
+        }
+        Ok(())
+    }
+
+    /// Runs a single frame without a window: dispatches `events`, then the
+    /// fixed updates due since the previous step, `update` and `render`.
+    /// Together with `FakeClock` this makes the loop deterministic.
+    pub fn step(&mut self, app: &mut impl App, events: &[Event]) {
+        let now = self.clock.now();
+        let previous_frame = self.last_frame.replace(now);
+        let frame_time = previous_frame
+            .map_or(Duration::ZERO, |last| now.saturating_sub(last))
+            .min(self.max_frame_time);
+
+        for event in events {
+            app.on_event(event);
+        }
+
+        self.accumulator += frame_time;
+        let mut fixed_steps = 0;
+        while self.accumulator >= self.fixed_timestep && fixed_steps < self.max_fixed_steps {
+            app.fixed_update(self.fixed_timestep.as_secs_f32());
+            self.accumulator -= self.fixed_timestep;
+            fixed_steps += 1;
+        }
+        if fixed_steps == self.max_fixed_steps {
+            // just below one step, the next frame starts with a fixed update
+            let limit = self.fixed_timestep.saturating_sub(Duration::from_nanos(1));
+            self.accumulator = self.accumulator.min(limit);
+        }
+
+        // the first frame has no duration, it would skew the statistics
+        if previous_frame.is_some() {
+            self.stats.record(frame_time, fixed_steps);
+        }
+        app.update(frame_time.as_secs_f32());
+        let interpolation = self.accumulator.as_secs_f64() / self.fixed_timestep.as_secs_f64();
+        app.render((interpolation as f32).min(MAX_INTERPOLATION));
+    }
+
+    /// Sleeps for the rest of the frame when a frame cap is set
+    pub fn wait_for_next_frame(&mut self) {
+        let (Some(frame_cap), Some(frame_start)) = (self.frame_cap, self.last_frame) else {
+            return;
+        };
+
+        let target = Duration::from_secs(1) / frame_cap;
+        let elapsed = self.clock.now().saturating_sub(frame_start);
+        if elapsed < target {
+            self.clock.sleep(target - elapsed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::clock::FakeClock;
+
+    #[derive(Default)]
+    struct CountingApp {
+        events: usize,
+        fixed_updates: usize,
+        updates: Vec<f32>,
+        interpolations: Vec<f32>,
+    }
+
+    impl App for CountingApp {
+        fn on_event(&mut self, _event: &Event) {
+            self.events += 1;
+        }
+
+        fn fixed_update(&mut self, _dt: f32) {
+            self.fixed_updates += 1;
+        }
+
+        fn update(&mut self, dt: f32) {
+            self.updates.push(dt);
+        }
+
+        fn render(&mut self, interpolation: f32) {
+            self.interpolations.push(interpolation);
+        }
+    }
+
+    fn fake_runner() -> Runner<FakeClock> {
+        let mut runner = Runner::with_clock(FakeClock::new());
+        runner.set_fixed_timestep(Duration::from_millis(10));
+        runner
+    }
+
+    #[test]
+    fn test_runner_fixed_timestep_accumulator() {
+        let mut runner = fake_runner();
+        let mut app = CountingApp::default();
+
+        runner.step(&mut app, &[]);
+        assert_eq!(0, app.fixed_updates);
+
+        runner.get_clock_mut().advance(Duration::from_millis(25));
+        runner.step(&mut app, &[Event::Focused(true)]);
+        assert_eq!(2, app.fixed_updates);
+        assert_eq!(1, app.events);
+        assert!((app.interpolations[1] - 0.5).abs() < 1e-6);
+
+        runner.get_clock_mut().advance(Duration::from_millis(5));
+        runner.step(&mut app, &[]);
+        assert_eq!(3, app.fixed_updates);
+        assert!((app.updates[2] - 0.005).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_runner_limits_fixed_steps() {
+        let mut runner = fake_runner();
+        runner.set_max_fixed_steps(3);
+        let mut app = CountingApp::default();
+
+        runner.step(&mut app, &[]);
+        runner.get_clock_mut().advance(Duration::from_millis(100));
+        runner.step(&mut app, &[]);
+        assert_eq!(3, app.fixed_updates);
+        assert_eq!(3, runner.get_stats().get_fixed_steps());
+
+        runner.get_clock_mut().advance(Duration::from_millis(5));
+        runner.step(&mut app, &[]);
+        assert_eq!(4, app.fixed_updates);
+    }
+
+    #[test]
+    fn test_runner_spiral_of_death_interpolation() {
+        let mut runner = fake_runner();
+        runner.set_fixed_timestep(Duration::from_secs(1));
+        runner.set_max_fixed_steps(2);
+        runner.set_max_frame_time(Duration::from_secs(10));
+        let mut app = CountingApp::default();
+
+        runner.step(&mut app, &[]);
+        for _ in 0..3 {
+            runner.get_clock_mut().advance(Duration::from_secs(5));
+            runner.step(&mut app, &[]);
+        }
+        assert_eq!(6, app.fixed_updates);
+        assert!(app
+            .interpolations
+            .iter()
+            .all(|interpolation| (0.0..1.0).contains(interpolation)));
+        assert!(app.interpolations[3] > 0.99);
+    }
+
+    #[test]
+    fn test_runner_frame_cap() {
+        let mut runner = fake_runner();
+        runner.set_frame_cap(Some(50));
+        let mut app = CountingApp::default();
+
+        runner.step(&mut app, &[]);
+        runner.get_clock_mut().advance(Duration::from_millis(5));
+        runner.wait_for_next_frame();
+        runner.step(&mut app, &[]);
+
+        assert_eq!(Duration::from_millis(20), runner.get_clock().now());
+        assert_eq!(Duration::from_millis(20), runner.get_stats().get_dt());
+        assert_eq!(1, runner.get_stats().get_frame_count());
+        assert!((runner.get_stats().get_fps() - 50.0).abs() < 1e-3);
+    }
+}
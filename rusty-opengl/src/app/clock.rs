@@ -0,0 +1,66 @@
+use std::time::{Duration, Instant};
+
+/// Time source of `Runner`, time is measured from an arbitrary start point
+pub trait Clock {
+    fn now(&self) -> Duration;
+    fn sleep(&mut self, duration: Duration);
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+/// Clock that only moves when told to, for deterministic tests
+#[derive(Clone, Debug, Default)]
+pub struct FakeClock {
+    now: Duration,
+}
+
+impl SystemClock {
+    #[must_use]
+    pub fn new() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+impl FakeClock {
+    #[must_use]
+    pub fn new() -> Self {
+        FakeClock {
+            now: Duration::ZERO,
+        }
+    }
+
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Sleeping advances the fake time instantly
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration);
+    }
+}
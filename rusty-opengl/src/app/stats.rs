@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Frame times of the most recent frames
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    history: VecDeque<Duration>,
+    capacity: usize,
+    frame_count: u64,
+    fixed_steps: u32,
+}
+
+impl FrameStats {
+    pub const DEFAULT_HISTORY: usize = 120;
+
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        FrameStats {
+            history: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            frame_count: 0,
+            fixed_steps: 0,
+        }
+    }
+
+    pub fn record(&mut self, frame_time: Duration, fixed_steps: u32) {
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame_time);
+        self.frame_count += 1;
+        self.fixed_steps = fixed_steps;
+    }
+
+    /// Duration of the last frame
+    #[must_use]
+    pub fn get_dt(&self) -> Duration {
+        self.history.back().copied().unwrap_or(Duration::ZERO)
+    }
+
+    /// Average over the history, 0 until a frame with a non-zero duration
+    #[must_use]
+    pub fn get_fps(&self) -> f32 {
+        let average = self.get_average_frame_time().as_secs_f32();
+        if average <= f32::EPSILON {
+            return 0.0;
+        }
+        1.0 / average
+    }
+
+    #[must_use]
+    pub fn get_average_frame_time(&self) -> Duration {
+        if self.history.is_empty() {
+            return Duration::ZERO;
+        }
+        self.history.iter().sum::<Duration>() / u32::try_from(self.history.len()).unwrap()
+    }
+
+    #[must_use]
+    pub fn get_max_frame_time(&self) -> Duration {
+        self.history.iter().max().copied().unwrap_or(Duration::ZERO)
+    }
+
+    /// Oldest frame first
+    #[must_use]
+    pub fn get_history(&self) -> &VecDeque<Duration> {
+        &self.history
+    }
+
+    #[must_use]
+    pub fn get_frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Number of `fixed_update` calls during the last frame
+    #[must_use]
+    pub fn get_fixed_steps(&self) -> u32 {
+        self.fixed_steps
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(FrameStats::DEFAULT_HISTORY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_stats_history() {
+        let mut stats = FrameStats::new(2);
+        stats.record(Duration::from_millis(10), 0);
+        stats.record(Duration::from_millis(30), 1);
+        stats.record(Duration::from_millis(10), 2);
+
+        assert_eq!(3, stats.get_frame_count());
+        assert_eq!(2, stats.get_history().len());
+        assert_eq!(Duration::from_millis(10), stats.get_dt());
+        assert_eq!(Duration::from_millis(20), stats.get_average_frame_time());
+        assert_eq!(Duration::from_millis(30), stats.get_max_frame_time());
+        assert!((stats.get_fps() - 50.0).abs() < 1e-3);
+        assert_eq!(2, stats.get_fixed_steps());
+    }
+}
@@ -0,0 +1,349 @@
+use crate::config::Window;
+use crate::events::Event;
+use crate::math::matrix::Mat4;
+use crate::math::vector::Vec2;
+
+/// How many world units fit on the screen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Scaling {
+    /// One world unit is one framebuffer pixel, resizes keep the world point
+    /// in the bottom-left corner in place
+    Pixels,
+    /// The visible height is fixed in world units, the width follows the
+    /// aspect ratio of the framebuffer
+    FixedHeight(f32),
+}
+
+/// Orthographic camera for 2D scenes. Y points up, `position` is the world
+/// point shown in the middle of the framebuffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera2D {
+    scaling: Scaling,
+    framebuffer_size: (u32, u32),
+    /// Framebuffer pixels per window coordinate, above 1 on HiDPI screens
+    content_scale: f32,
+    position: Vec2,
+    zoom: f32,
+    rotation: f32,
+}
+
+impl Camera2D {
+    #[must_use]
+    pub fn new(scaling: Scaling, framebuffer_size: (u32, u32)) -> Self {
+        Camera2D {
+            scaling,
+            framebuffer_size,
+            content_scale: 1.0,
+            position: Vec2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// Pixel coordinates with the origin in the bottom-left corner
+    #[must_use]
+    pub fn pixels(framebuffer_size: (u32, u32)) -> Self {
+        let mut result = Camera2D::new(Scaling::Pixels, framebuffer_size);
+        result.position = Vec2::new(
+            framebuffer_size.0 as f32 / 2.0,
+            framebuffer_size.1 as f32 / 2.0,
+        );
+        result
+    }
+
+    /// Shows `[-1, 1]` vertically and as much horizontally as the aspect
+    /// ratio allows, so circles stay round on any window
+    #[must_use]
+    pub fn normalized(framebuffer_size: (u32, u32)) -> Self {
+        Camera2D::new(Scaling::FixedHeight(2.0), framebuffer_size)
+    }
+
+    /// Picks up framebuffer resizes and the content scale, call it once per
+    /// frame or on resize events
+    pub fn follow(&mut self, window: &Window) {
+        let (width, height) = window.get_framebuffer_size();
+        let (window_width, _) = window.get_window_size();
+        if width > 0 && window_width > 0 {
+            self.set_content_scale(width as f32 / window_width as f32);
+        }
+        self.resize(
+            u32::try_from(width).unwrap_or(0),
+            u32::try_from(height).unwrap_or(0),
+        );
+    }
+
+    /// Applies `Event::Resized`, other events are ignored
+    pub fn handle_event(&mut self, event: &Event) {
+        if let Event::Resized { width, height } = event {
+            self.resize(*width, *height);
+        }
+    }
+
+    /// Pixel cameras keep the world point in the bottom-left corner in
+    /// place, the others the one in the middle of the screen
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let previous_half = self.get_visible_size() / 2.0;
+        self.framebuffer_size = (width, height);
+        if self.scaling == Scaling::Pixels {
+            let offset = self.get_visible_size() / 2.0 - previous_half;
+            self.position += self.rotate_offset(offset);
+        }
+    }
+
+    /// Framebuffer pixels per window coordinate, used by `screen_to_world`.
+    /// `follow` sets it, non-positive values are ignored.
+    pub fn set_content_scale(&mut self, scale: f32) {
+        if scale > 0.0 && scale.is_finite() {
+            self.content_scale = scale;
+        }
+    }
+
+    pub fn pan(&mut self, offset: Vec2) {
+        self.position += offset;
+    }
+
+    /// Multiplies the zoom, values above 1 magnify
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.set_zoom(self.zoom * factor);
+    }
+
+    /// Rotates the view counter-clockwise, angle in radians
+    pub fn rotate(&mut self, angle: f32) {
+        self.rotation += angle;
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    /// Non-positive values are ignored
+    pub fn set_zoom(&mut self, zoom: f32) {
+        if zoom > 0.0 && zoom.is_finite() {
+            self.zoom = zoom;
+        }
+    }
+
+    pub fn set_rotation(&mut self, angle: f32) {
+        self.rotation = angle;
+    }
+
+    #[must_use]
+    pub fn get_position(&self) -> Vec2 {
+        self.position
+    }
+
+    #[must_use]
+    pub fn get_zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    #[must_use]
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    #[must_use]
+    pub fn get_framebuffer_size(&self) -> (u32, u32) {
+        self.framebuffer_size
+    }
+
+    #[must_use]
+    pub fn get_content_scale(&self) -> f32 {
+        self.content_scale
+    }
+
+    /// Size of the visible area in world units
+    #[must_use]
+    pub fn get_visible_size(&self) -> Vec2 {
+        let (width, height) = (
+            self.framebuffer_size.0.max(1) as f32,
+            self.framebuffer_size.1.max(1) as f32,
+        );
+        let size = match self.scaling {
+            Scaling::Pixels => Vec2::new(width, height),
+            Scaling::FixedHeight(visible_height) => {
+                Vec2::new(visible_height * width / height, visible_height)
+            }
+        };
+        size / self.zoom
+    }
+
+    #[must_use]
+    pub fn get_projection(&self) -> Mat4 {
+        let half = self.get_visible_size() / 2.0;
+        Mat4::orthographic(-half.x, half.x, -half.y, half.y, -1.0, 1.0)
+    }
+
+    #[must_use]
+    pub fn get_view(&self) -> Mat4 {
+        Mat4::from_rotation_z(-self.rotation) * Mat4::from_translation(-self.position.extend(0.0))
+    }
+
+    /// Matrix uploaded to the `viewProjection` uniform of the built-in shaders
+    #[must_use]
+    pub fn get_view_projection(&self) -> Mat4 {
+        self.get_projection() * self.get_view()
+    }
+
+    /// Converts window coordinates (origin in the top-left corner, y
+    /// pointing down, e.g. the cursor position) to world coordinates. They
+    /// are scaled to framebuffer pixels with the content scale.
+    /// Undoes the view projection step by step instead of inverting the
+    /// matrix, whose tiny determinant for pixel cameras costs precision.
+    #[must_use]
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        let (width, height) = self.get_window_size();
+        let half = self.get_visible_size() / 2.0;
+        let offset = Vec2::new(
+            (2.0 * point.x / width - 1.0) * half.x,
+            (1.0 - 2.0 * point.y / height) * half.y,
+        );
+        self.position + self.rotate_offset(offset)
+    }
+
+    /// Inverse of `screen_to_world`
+    #[must_use]
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        let ndc = self
+            .get_view_projection()
+            .transform_point(point.extend(0.0));
+        let (width, height) = self.get_window_size();
+        Vec2::new((ndc.x + 1.0) / 2.0 * width, (1.0 - ndc.y) / 2.0 * height)
+    }
+
+    /// Size of the window in window coordinates
+    fn get_window_size(&self) -> (f32, f32) {
+        (
+            self.framebuffer_size.0.max(1) as f32 / self.content_scale,
+            self.framebuffer_size.1.max(1) as f32 / self.content_scale,
+        )
+    }
+
+    /// Turns an offset seen on the screen into world units
+    fn rotate_offset(&self, offset: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vector::Vec3;
+
+    fn assert_vec2_eq(expected: Vec2, result: Vec2) {
+        assert!(
+            (expected - result).length() < 1e-3,
+            "expected {expected:?}, got {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_camera_pixels_corners() {
+        let camera = Camera2D::pixels((800, 600));
+        let matrix = camera.get_view_projection();
+        let bottom_left = matrix.transform_point(Vec3::ZERO);
+        let top_right = matrix.transform_point(Vec3::new(800.0, 600.0, 0.0));
+        assert_vec2_eq(Vec2::new(-1.0, -1.0), bottom_left.truncate());
+        assert_vec2_eq(Vec2::new(1.0, 1.0), top_right.truncate());
+    }
+
+    #[test]
+    fn test_camera_normalized_keeps_aspect_ratio() {
+        let mut camera = Camera2D::normalized((900, 600));
+        assert_vec2_eq(Vec2::new(3.0, 2.0), camera.get_visible_size());
+
+        camera.resize(600, 900);
+        let size = camera.get_visible_size();
+        assert_vec2_eq(Vec2::new(2.0 * 600.0 / 900.0, 2.0), size);
+    }
+
+    #[test]
+    fn test_camera_handle_resize_event() {
+        let mut camera = Camera2D::normalized((900, 600));
+        camera.handle_event(&Event::Focused(true));
+        camera.handle_event(&Event::Resized {
+            width: 600,
+            height: 600,
+        });
+        assert_eq!((600, 600), camera.get_framebuffer_size());
+        assert_vec2_eq(Vec2::new(2.0, 2.0), camera.get_visible_size());
+    }
+
+    #[test]
+    fn test_camera_screen_world_round_trip() {
+        let mut camera = Camera2D::pixels((800, 600));
+        camera.pan(Vec2::new(-100.0, 50.0));
+        camera.zoom_by(2.0);
+        camera.rotate(0.4);
+
+        let center = camera.screen_to_world(Vec2::new(400.0, 300.0));
+        assert_vec2_eq(camera.get_position(), center);
+
+        let world = camera.screen_to_world(Vec2::new(10.0, 20.0));
+        assert_vec2_eq(Vec2::new(10.0, 20.0), camera.world_to_screen(world));
+    }
+
+    #[test]
+    fn test_camera_screen_to_world_flips_y() {
+        let camera = Camera2D::pixels((800, 600));
+        assert_vec2_eq(Vec2::new(0.0, 600.0), camera.screen_to_world(Vec2::ZERO));
+    }
+
+    #[test]
+    fn test_camera_screen_to_world_zoomed_out_pixels() {
+        let mut camera = Camera2D::pixels((1920, 1080));
+        camera.set_zoom(0.25);
+        assert_vec2_eq(
+            Vec2::new(960.0, 540.0),
+            camera.screen_to_world(Vec2::new(960.0, 540.0)),
+        );
+        assert_vec2_eq(
+            Vec2::new(960.0 - 3840.0, 540.0 + 2160.0),
+            camera.screen_to_world(Vec2::ZERO),
+        );
+        let world = camera.screen_to_world(Vec2::new(100.0, 900.0));
+        assert_vec2_eq(Vec2::new(100.0, 900.0), camera.world_to_screen(world));
+    }
+
+    #[test]
+    fn test_camera_pixels_resize_keeps_origin() {
+        let mut camera = Camera2D::pixels((800, 600));
+        camera.resize(1000, 500);
+        let matrix = camera.get_view_projection();
+        let bottom_left = matrix.transform_point(Vec3::ZERO);
+        let top_right = matrix.transform_point(Vec3::new(1000.0, 500.0, 0.0));
+        assert_vec2_eq(Vec2::new(-1.0, -1.0), bottom_left.truncate());
+        assert_vec2_eq(Vec2::new(1.0, 1.0), top_right.truncate());
+
+        camera.zoom_by(2.0);
+        camera.rotate(0.3);
+        let origin = camera.screen_to_world(Vec2::new(0.0, 500.0));
+        camera.resize(600, 700);
+        assert_vec2_eq(origin, camera.screen_to_world(Vec2::new(0.0, 700.0)));
+    }
+
+    #[test]
+    fn test_camera_screen_to_world_content_scale() {
+        let mut camera = Camera2D::pixels((1600, 1200));
+        camera.set_content_scale(2.0);
+        assert_vec2_eq(
+            Vec2::new(800.0, 600.0),
+            camera.screen_to_world(Vec2::new(400.0, 300.0)),
+        );
+        assert_vec2_eq(Vec2::new(0.0, 1200.0), camera.screen_to_world(Vec2::ZERO));
+        let world = camera.screen_to_world(Vec2::new(10.0, 20.0));
+        assert_vec2_eq(Vec2::new(10.0, 20.0), camera.world_to_screen(world));
+    }
+
+    #[test]
+    fn test_camera_zoom_ignores_invalid_values() {
+        let mut camera = Camera2D::normalized((100, 100));
+        camera.set_zoom(0.0);
+        camera.zoom_by(-1.0);
+        assert!((camera.get_zoom() - 1.0).abs() < f32::EPSILON);
+    }
+}
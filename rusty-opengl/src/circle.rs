@@ -1,15 +1,23 @@
+use crate::camera::Camera2D;
 use crate::color::RGBA;
 use crate::config::Resolution;
+use crate::error::Error;
+use crate::math::matrix::Mat4;
+use crate::math::transform::Transform;
+use crate::polygons::databuffer::BufferUsage;
 use crate::shaders::shader_program::ShaderProgram;
 use crate::shaders::utils::create_shader_program;
 use std::rc::Rc;
 
 pub struct Circle {
     center: (f32, f32),
+    radius: f32,
     radius_width: f32,
     radius_height: f32,
     color: RGBA,
     segments: u16,
+    usage: BufferUsage,
+    transform: Transform,
     circle_data_buffer: Option<CircleDataBuffer>,
 }
 
@@ -18,58 +26,119 @@ struct CircleDataBuffer {
     vbo: u32,
     segments: u16,
     shader: ShaderProgram,
+    usage: BufferUsage,
 }
 
 impl Circle {
     const DEFAULT_SEGMENTS: u16 = 32;
 
+    /// `center` and `radius` are in normalized device coordinates when drawn
+    /// with `draw`, or in world units when drawn with `draw_with_camera`
     #[must_use]
     pub fn new(center: (f32, f32), mut radius: f32, color: RGBA, segments: Option<u16>) -> Self {
         let segments_value = segments.unwrap_or(Circle::DEFAULT_SEGMENTS);
-        radius = Circle::normalized_radius(radius);
+        radius = Circle::valid_radius(radius);
 
         Circle {
             center,
+            radius,
             radius_width: radius,
             radius_height: radius,
             color,
             segments: segments_value,
+            usage: BufferUsage::Static,
+            transform: Transform::IDENTITY,
             circle_data_buffer: None,
         }
     }
 
+    /// Draws in normalized device coordinates
     pub fn draw(&self) {
-        if self.circle_data_buffer.is_some() {
-            self.circle_data_buffer.as_ref().unwrap().draw();
+        if let Some(circle_data_buffer) = &self.circle_data_buffer {
+            circle_data_buffer.draw(&self.transform, &Mat4::IDENTITY);
         }
     }
 
-    pub fn init(&mut self) {
+    /// Draws in the world coordinates of `camera`, which also keeps the
+    /// circle round on non-square framebuffers
+    pub fn draw_with_camera(&self, camera: &Camera2D) {
+        if let Some(circle_data_buffer) = &self.circle_data_buffer {
+            circle_data_buffer.draw(&self.transform, &camera.get_view_projection());
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the circle shader program could not be loaded,
+    /// compiled or linked
+    pub fn init(&mut self) -> Result<(), Error> {
         if self.circle_data_buffer.is_none() {
-            self.circle_data_buffer = Some(CircleDataBuffer::new(self.segments));
+            let mut circle_data_buffer = CircleDataBuffer::new(self.segments, self.usage)?;
             let raw_data = self.calculate_raw_data();
-            self.circle_data_buffer
-                .as_mut()
-                .unwrap()
-                .init(&raw_data, &self.color);
+            circle_data_buffer.init(&raw_data, &self.color)?;
+            self.circle_data_buffer = Some(circle_data_buffer);
         }
+        Ok(())
+    }
+
+    /// Usage hint of the vertex buffer, takes effect when set before `init`
+    pub fn set_usage(&mut self, usage: BufferUsage) {
+        self.usage = usage;
+    }
+
+    /// Moves the circle, an initialized circle uploads its vertices again
+    pub fn set_center(&mut self, center: (f32, f32)) {
+        self.center = center;
+        self.update_buffer();
+    }
+
+    /// Resizes the circle, this resets any correction done by `adjust_radius`
+    pub fn set_radius(&mut self, radius: f32) {
+        let radius = Circle::valid_radius(radius);
+        self.radius = radius;
+        self.radius_width = radius;
+        self.radius_height = radius;
+        self.update_buffer();
+    }
+
+    #[must_use]
+    pub fn get_center(&self) -> (f32, f32) {
+        self.center
     }
 
+    /// Applied on top of the center and radius, without uploading vertices
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    #[must_use]
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    /// Squeezes the circle so it looks round when drawn with `draw` on a
+    /// non-square framebuffer. The correction is computed from the original
+    /// radius, so it can be applied again after every resize.
+    #[deprecated(note = "draw with a `Camera2D` instead, it follows framebuffer resizes")]
     pub fn adjust_radius(&mut self, resolution: Rc<Resolution>) {
-        if resolution.width == resolution.height {
-            return;
+        self.radius_width = self.radius;
+        self.radius_height = self.radius;
+        if resolution.width > resolution.height {
+            self.radius_width = resolution.height as f32 * self.radius / resolution.width as f32;
+        } else if resolution.width < resolution.height {
+            self.radius_height = resolution.width as f32 * self.radius / resolution.height as f32;
         }
+        self.update_buffer();
+    }
 
-        match resolution.width > resolution.height {
-            true => {
-                self.radius_width =
-                    resolution.height as f32 * self.radius_width / resolution.width as f32
-            }
-            false => {
-                self.radius_height =
-                    resolution.width as f32 * self.radius_height / resolution.height as f32
-            }
-        };
+    fn update_buffer(&self) {
+        if let Some(circle_data_buffer) = &self.circle_data_buffer {
+            circle_data_buffer.update(&self.calculate_raw_data());
+        }
     }
 
     fn calculate_raw_data(&self) -> Vec<f32> {
@@ -86,34 +155,13 @@ impl Circle {
     }
 
     fn add_center_point(&self, mut buffer: Vec<f32>) -> Vec<f32> {
-        let center = self.normalized_center_coordinates();
-        buffer.append(&mut vec![center.0, center.1, 0.0]);
+        buffer.append(&mut vec![self.center.0, self.center.1, 0.0]);
         buffer
     }
 
-    fn normalized_center_coordinates(&self) -> (f32, f32) {
-        let mut result = self.center;
-        if self.center.0 > 1_f32 {
-            result.0 = 1_f32;
-        }
-        if self.center.1 > 1_f32 {
-            result.1 = 1_f32;
-        }
-        if self.center.0 < -1_f32 {
-            result.0 = -1_f32;
-        }
-        if self.center.1 < -1_f32 {
-            result.1 = -1_f32;
-        }
-
-        result
-    }
-
-    fn normalized_radius(radius: f32) -> f32 {
+    fn valid_radius(radius: f32) -> f32 {
         if radius < 0_f32 {
             return 0_f32;
-        } else if radius > 1_f32 {
-            return 1_f32;
         }
 
         radius
@@ -124,11 +172,12 @@ impl Circle {
             return buffer;
         }
 
+        let center = self.center;
         let angle = 360_f32 / f32::from(self.segments);
         let mut current_angle = angle;
         for _ in 0..self.segments {
-            let x = self.radius_width * f32::sin(f32::to_radians(current_angle));
-            let y = self.radius_height * f32::cos(f32::to_radians(current_angle));
+            let x = center.0 + self.radius_width * f32::sin(f32::to_radians(current_angle));
+            let y = center.1 + self.radius_height * f32::cos(f32::to_radians(current_angle));
 
             buffer.append(&mut vec![x, y, 0.0]);
             current_angle += angle;
@@ -138,50 +187,75 @@ impl Circle {
 }
 
 impl CircleDataBuffer {
-    pub fn new(segments: u16) -> Self {
-        let shader = create_shader_program("colored_circle.vert", "basic_colored.frag");
-        CircleDataBuffer {
+    pub fn new(segments: u16, usage: BufferUsage) -> Result<Self, Error> {
+        let shader = create_shader_program("colored_circle.vert", "basic.frag")?;
+        Ok(CircleDataBuffer {
             vao: 0,
             vbo: 0,
             shader,
             segments,
-        }
+            usage,
+        })
     }
 
-    pub fn init(&mut self, data: &[f32], color: &RGBA) {
-        self.compile_shader();
+    pub fn init(&mut self, data: &[f32], color: &RGBA) -> Result<(), Error> {
+        self.shader.compile()?;
         self.generate_buffers();
         self.bind();
-        CircleDataBuffer::create_buffer_array(data);
+        self.create_buffer_array(data);
         CircleDataBuffer::set_position_attribute_ptr();
         //TODO ADD AS IMPROVEMENT
         //Do not know why it's now working :( Going to set color via uniform variable
         //CircleDataBuffer::set_color_attribute_ptr();
-        self.set_color_via_uniform(color);
+        self.shader.set_uniform("circleColor", color)?;
+        self.shader.require_uniforms(&["model", "viewProjection"])?;
         CircleDataBuffer::unbind();
+        Ok(())
     }
 
-    pub fn draw(&self) {
+    pub fn draw(&self, transform: &Transform, view_projection: &Mat4) {
+        self.shader.activate();
+        // both are checked in `init`
+        self.shader
+            .set_uniform("model", transform)
+            .expect("circle shader declares model");
+        self.shader
+            .set_uniform("viewProjection", view_projection)
+            .expect("circle shader declares viewProjection");
         unsafe {
-            self.shader.activate();
             gl::BindVertexArray(self.vao);
             gl::DrawArrays(gl::TRIANGLE_FAN, 1, i32::from(self.segments));
             ShaderProgram::deactivate();
         }
     }
 
-    fn compile_shader(&mut self) {
-        self.shader.compile();
+    /// The number of segments is fixed, so the data always has the size of
+    /// the initial upload and can be written in place
+    pub fn update(&self, data: &[f32]) {
+        let size: isize = std::mem::size_of_val(data).try_into().unwrap();
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            if self.usage != BufferUsage::Static {
+                gl::BufferData(gl::ARRAY_BUFFER, size, std::ptr::null(), self.usage as u32);
+            }
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                size,
+                data.as_ptr().cast::<std::ffi::c_void>(),
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
     }
 
-    fn create_buffer_array(data: &[f32]) {
-        let size = data.len() * std::mem::size_of::<f32>();
+    fn create_buffer_array(&self, data: &[f32]) {
+        let size = std::mem::size_of_val(data);
         unsafe {
             gl::BufferData(
                 gl::ARRAY_BUFFER,
                 size.try_into().unwrap(),
                 data.as_ptr().cast::<std::ffi::c_void>(),
-                gl::STATIC_DRAW,
+                self.usage as u32,
             );
         }
     }
@@ -219,10 +293,6 @@ impl CircleDataBuffer {
         }
     }
 
-    fn set_color_via_uniform(&self, color: &RGBA) -> bool {
-        self.shader.set_uniform4f_variable("circleColor", color)
-    }
-
     fn bind(&self) {
         unsafe {
             gl::BindVertexArray(self.vao);
@@ -290,4 +360,55 @@ mod tests {
         let expected_len = Circle::DEFAULT_SEGMENTS as usize * 3 + 7;
         assert_eq!(expected_len, raw_data.len());
     }
+
+    #[test]
+    fn test_calculate_raw_data_world_units() {
+        let circle = Circle::new(
+            (400_f32, 300_f32),
+            50_f32,
+            RGBA::from_hex(0x00_00_FF_FF),
+            Some(4),
+        );
+        let raw_data = circle.calculate_raw_data();
+        assert_eq!(&[400_f32, 300_f32, 0_f32], &raw_data[4..7]);
+        assert!((raw_data[7] - 450_f32).abs() < 1e-3);
+        assert!((raw_data[8] - 300_f32).abs() < 1e-3);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_adjust_radius_after_resize() {
+        let mut circle = Circle::new(
+            (0_f32, 0_f32),
+            0.5_f32,
+            RGBA::from_hex(0x00_00_FF_FF),
+            Some(4),
+        );
+        circle.adjust_radius(Rc::new(Resolution {
+            width: 1000,
+            height: 500,
+        }));
+        circle.adjust_radius(Rc::new(Resolution {
+            width: 500,
+            height: 1000,
+        }));
+        assert!((circle.radius_width - 0.5).abs() < 1e-6);
+        assert!((circle.radius_height - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_calculate_raw_data_moved_center() {
+        let mut circle = Circle::new(
+            (0_f32, 0_f32),
+            0.5_f32,
+            RGBA::from_hex(0x00_00_FF_FF),
+            Some(4),
+        );
+        circle.set_center((0.25_f32, -0.5_f32));
+        let raw_data = circle.calculate_raw_data();
+        assert_eq!(&[0.25_f32, -0.5_f32, 0_f32], &raw_data[4..7]);
+        let top = &raw_data[16..19];
+        assert!((top[0] - 0.25).abs() < 1e-6);
+        assert!((top[1] - 0.0).abs() < 1e-6);
+    }
 }
@@ -1,6 +1,15 @@
+pub mod window_builder;
+
+use crate::error::Error;
+use crate::events::Event;
+use crate::gl_state;
+use crate::readback::{read_pixels, ColorBuffer};
 use gl;
 use glfw;
 use glfw::Context;
+use image::RgbaImage;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::mpsc::Receiver;
 
@@ -12,15 +21,20 @@ pub struct Resolution {
 pub struct Window {
     window: glfw::Window,
     resolution: Rc<Resolution>,
+    is_invisible: bool,
+    is_srgb: bool,
+    swap_interval: Option<u32>,
+    resize_callback: Option<Box<dyn FnMut(u32, u32)>>,
 }
 
 pub struct Glfw {
     glfw: glfw::Glfw,
+    is_invisible: bool,
 }
 
-#[allow(dead_code)]
 pub struct WindowEvents {
-    events: Receiver<(f64, glfw::WindowEvent)>,
+    events: Option<Receiver<(f64, glfw::WindowEvent)>>,
+    injected: VecDeque<Event>,
 }
 
 pub fn set_background_color(red: f32, green: f32, blue: f32) {
@@ -31,12 +45,47 @@ pub fn set_background_color(red: f32, green: f32, blue: f32) {
 }
 
 impl Glfw {
-    #[must_use]
+    /// # Errors
+    ///
+    /// Will return `Err` if GLFW could not be initialized
+    pub fn new() -> Result<Self, Error> {
+        let glfw = glfw::init(glfw::LOG_ERRORS)
+            .map_err(|error| Error::WindowCreation(error.to_string()))?;
+        let mut result = Glfw {
+            glfw,
+            is_invisible: false,
+        };
+        result.apply_default_hints();
+        Ok(result)
+    }
+
+    /// Creates a context whose windows are never shown on the screen.
+    ///
+    /// This is not headless rendering: the windows are real GLFW windows, so a
+    /// display server is still required. On machines without one, run under
+    /// a virtual display such as Xvfb (e.g. with Mesa llvmpipe).
+    ///
+    /// Windows are single buffered, so everything drawn lands directly in the
+    /// front buffer and can be read back without presenting it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if GLFW could not be initialized
+    pub fn invisible() -> Result<Self, Error> {
+        let mut result = Glfw::new()?;
+        result.is_invisible = true;
+        result.apply_invisible_hints();
+        Ok(result)
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the window or its OpenGL context could not be created
     pub fn create_window(
         &self,
         resolution: Rc<Resolution>,
         window_name: &str,
-    ) -> (Window, WindowEvents) {
+    ) -> Result<(Window, WindowEvents), Error> {
         let (window, events) = self
             .glfw
             .create_window(
@@ -45,53 +94,269 @@ impl Glfw {
                 window_name,
                 glfw::WindowMode::Windowed,
             )
-            .expect("Failed to create GLFW window");
-        (Window { window, resolution }, WindowEvents { events })
+            .ok_or_else(|| {
+                Error::WindowCreation(format!(
+                    "GLFW could not create a {}x{} window",
+                    resolution.width, resolution.height
+                ))
+            })?;
+        Ok(self.wrap_window(window, events, resolution))
     }
 
     pub fn poll_events(&mut self) {
         self.glfw.poll_events();
     }
+
+    #[must_use]
+    pub fn is_invisible(&self) -> bool {
+        self.is_invisible
+    }
+
+    /// OpenGL 3.3 core, forward compatible, used by `create_window`
+    fn apply_default_hints(&mut self) {
+        self.glfw.default_window_hints();
+        self.glfw
+            .window_hint(glfw::WindowHint::ContextVersion(3, 3));
+        self.glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+            glfw::OpenGlProfileHint::Core,
+        ));
+        self.glfw
+            .window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+        self.apply_invisible_hints();
+    }
+
+    fn apply_invisible_hints(&mut self) {
+        if self.is_invisible {
+            self.glfw.window_hint(glfw::WindowHint::Visible(false));
+            self.glfw.window_hint(glfw::WindowHint::FocusOnShow(false));
+            self.glfw.window_hint(glfw::WindowHint::DoubleBuffer(false));
+        }
+    }
+
+    fn wrap_window(
+        &self,
+        window: glfw::Window,
+        events: Receiver<(f64, glfw::WindowEvent)>,
+        resolution: Rc<Resolution>,
+    ) -> (Window, WindowEvents) {
+        let (width, height) = window.get_framebuffer_size();
+        gl_state::reset_context(window.window_ptr() as usize, [0, 0, width, height]);
+        (
+            Window {
+                window,
+                resolution,
+                is_invisible: self.is_invisible,
+                is_srgb: false,
+                swap_interval: None,
+                resize_callback: None,
+            },
+            WindowEvents {
+                events: Some(events),
+                injected: VecDeque::new(),
+            },
+        )
+    }
 }
 
 impl Default for Glfw {
+    /// # Panics
+    ///
+    /// Will panic if GLFW could not be initialized, use `Glfw::new` to handle it
     fn default() -> Self {
-        let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
-        glfw.window_hint(glfw::WindowHint::ContextVersion(3, 3));
-        glfw.window_hint(glfw::WindowHint::OpenGlProfile(
-            glfw::OpenGlProfileHint::Core,
-        ));
-        glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
-        Glfw { glfw }
+        Glfw::new().expect("Failed to initialize GLFW")
     }
 }
 
 impl Window {
+    /// Makes the context current and applies the swap interval requested
+    /// with `WindowBuilder`
     pub fn set_current(&mut self) {
         self.window.make_current();
+        gl_state::make_current(self.window.window_ptr() as usize);
+        if let Some(interval) = self.swap_interval {
+            self.window
+                .glfw
+                .set_swap_interval(glfw::SwapInterval::Sync(interval));
+        }
         self.window.set_key_polling(true);
+        self.window.set_mouse_button_polling(true);
+        self.window.set_cursor_pos_polling(true);
+        self.window.set_scroll_polling(true);
         self.window.set_framebuffer_size_polling(true);
+        self.window.set_focus_polling(true);
+        self.window.set_close_polling(true);
+        self.window.set_drag_and_drop_polling(true);
     }
 
     pub fn load_opengl_func_ptr(&mut self) {
         gl::load_with(|symbol| self.window.get_proc_address(symbol).cast());
+        if self.is_srgb {
+            unsafe {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+    }
+
+    /// Version of the created context, may be lower than requested when
+    /// `WindowBuilder` had to fall back
+    #[must_use]
+    pub fn get_gl_version(&self) -> (u32, u32) {
+        let version = self.window.get_context_version();
+        (
+            u32::try_from(version.major).unwrap_or(0),
+            u32::try_from(version.minor).unwrap_or(0),
+        )
     }
 
     pub fn is_running_window(&self) -> bool {
         !self.window.should_close()
     }
 
+    /// Makes `is_running_window` return `false`, e.g. to quit on a key press
+    pub fn set_should_close(&mut self, should_close: bool) {
+        self.window.set_should_close(should_close);
+    }
+
+    /// Presents the back buffer. Invisible windows are single buffered and
+    /// render straight into the front buffer, so this is a flush for them.
     pub fn swap_buffers(&mut self) {
-        self.window.swap_buffers();
+        if self.is_invisible {
+            unsafe {
+                gl::Flush();
+            }
+        } else {
+            self.window.swap_buffers();
+        }
     }
 
     pub fn get_framebuffer_size(&self) -> (i32, i32) {
         self.window.get_framebuffer_size()
     }
 
+    /// Size in window coordinates, used by cursor positions. Smaller than
+    /// the framebuffer on HiDPI screens.
+    #[must_use]
+    pub fn get_window_size(&self) -> (i32, i32) {
+        self.window.get_size()
+    }
+
+    /// Reads the window contents, top row first. Invisible windows are single
+    /// buffered, so they always read the front buffer.
+    #[must_use]
+    pub fn read_pixels(&self, buffer: ColorBuffer) -> RgbaImage {
+        let buffer = if self.is_invisible {
+            ColorBuffer::Front
+        } else {
+            buffer
+        };
+        let (width, height) = self.get_framebuffer_size();
+        read_pixels(
+            0,
+            buffer as u32,
+            u32::try_from(width).unwrap_or(0),
+            u32::try_from(height).unwrap_or(0),
+        )
+    }
+
+    /// Saves the front buffer, the format is deduced from the extension
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the image could not be encoded or written
+    pub fn save_screenshot(&self, path: &Path) -> Result<(), Error> {
+        self.read_pixels(ColorBuffer::Front)
+            .save(path)
+            .map_err(|error| Error::ImageEncode(error.to_string()))
+    }
+
+    /// Framebuffer size after the last processed resize, see `process_events`
     pub fn get_resolution(&self) -> Rc<Resolution> {
         self.resolution.clone()
     }
+
+    /// Called with the new framebuffer size after the viewport was updated
+    pub fn set_resize_callback(&mut self, callback: impl FnMut(u32, u32) + 'static) {
+        self.resize_callback = Some(Box::new(callback));
+    }
+
+    /// Drains `events`, applying framebuffer resizes to the viewport and the
+    /// resolution before returning them
+    pub fn process_events(&mut self, events: &mut WindowEvents) -> Vec<Event> {
+        let result = events.poll();
+        for event in &result {
+            self.handle_event(event);
+        }
+        result
+    }
+
+    /// Returns `true` if the event changed the framebuffer size
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Resized { width, height } => self.resize(*width, *height),
+            _ => false,
+        }
+    }
+
+    /// Queries the framebuffer size directly, for loops that do not process
+    /// events. Returns `true` if it changed.
+    pub fn sync_framebuffer_size(&mut self) -> bool {
+        let (width, height) = self.window.get_framebuffer_size();
+        self.resize(
+            u32::try_from(width).unwrap_or(0),
+            u32::try_from(height).unwrap_or(0),
+        )
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> bool {
+        if self.resolution.width == width && self.resolution.height == height {
+            return false;
+        }
+
+        self.resolution = Rc::new(Resolution { width, height });
+        gl_state::set_viewport([
+            0,
+            0,
+            i32::try_from(width).unwrap_or(i32::MAX),
+            i32::try_from(height).unwrap_or(i32::MAX),
+        ]);
+        if let Some(callback) = self.resize_callback.as_mut() {
+            callback(width, height);
+        }
+        true
+    }
+
+    #[must_use]
+    pub fn is_invisible(&self) -> bool {
+        self.is_invisible
+    }
+}
+
+impl WindowEvents {
+    /// Queue not connected to any window, it only returns injected events
+    #[must_use]
+    pub fn empty() -> Self {
+        WindowEvents {
+            events: None,
+            injected: VecDeque::new(),
+        }
+    }
+
+    /// Queues a synthetic event, returned by the next `poll` before the
+    /// events received from the window
+    pub fn inject(&mut self, event: Event) {
+        self.injected.push_back(event);
+    }
+
+    /// Drains all pending events, call it after `Glfw::poll_events`
+    pub fn poll(&mut self) -> Vec<Event> {
+        let mut result: Vec<Event> = self.injected.drain(..).collect();
+        if let Some(events) = &self.events {
+            result.extend(
+                glfw::flush_messages(events).filter_map(|(_, event)| Event::from_glfw(event)),
+            );
+        }
+        result
+    }
 }
 
 #[cfg(test)]
@@ -105,8 +370,29 @@ mod tests {
             width: 800,
             height: 600,
         });
-        let (mut window, _events) = glfw.create_window(resolution.clone(), "learn opengl");
+        let (mut window, _events) = glfw
+            .create_window(resolution.clone(), "learn opengl")
+            .unwrap();
+        window.set_current();
+        window.load_opengl_func_ptr();
+    }
+
+    #[test]
+    fn test_config_invisible_configuration() {
+        // invisible windows still need a display server
+        if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            return;
+        }
+        let glfw = Glfw::invisible().unwrap();
+        assert!(glfw.is_invisible());
+        let resolution = Rc::new(Resolution {
+            width: 320,
+            height: 240,
+        });
+        let (mut window, _events) = glfw.create_window(resolution, "invisible").unwrap();
         window.set_current();
         window.load_opengl_func_ptr();
+        assert!(window.is_invisible());
+        assert!(window.is_running_window());
     }
 }
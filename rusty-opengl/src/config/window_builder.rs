@@ -0,0 +1,303 @@
+use crate::config::{Glfw, Resolution, Window, WindowEvents};
+use crate::error::Error;
+use std::rc::Rc;
+use std::sync::mpsc::Receiver;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlProfile {
+    Core,
+    Compatibility,
+    Any,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    /// Exclusive full screen on the primary monitor, switching it to the
+    /// requested resolution
+    Fullscreen,
+    /// Covers the primary monitor at its current video mode
+    Borderless,
+}
+
+pub struct WindowBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    gl_version: (u32, u32),
+    min_gl_version: (u32, u32),
+    profile: GlProfile,
+    samples: Option<u32>,
+    depth_bits: Option<u32>,
+    stencil_bits: Option<u32>,
+    is_srgb: bool,
+    swap_interval: Option<u32>,
+    is_resizable: bool,
+    is_decorated: bool,
+    is_floating: bool,
+    mode: WindowMode,
+    position: Option<(i32, i32)>,
+}
+
+impl WindowBuilder {
+    /// Every OpenGL version with a core profile, newest first
+    const GL_VERSIONS: [(u32, u32); 9] = [
+        (4, 6),
+        (4, 5),
+        (4, 4),
+        (4, 3),
+        (4, 2),
+        (4, 1),
+        (4, 0),
+        (3, 3),
+        (3, 2),
+    ];
+
+    /// Windowed 3.3 core context, the same as `Glfw::create_window`
+    #[must_use]
+    pub fn new(title: &str, width: u32, height: u32) -> Self {
+        WindowBuilder {
+            title: String::from(title),
+            width,
+            height,
+            gl_version: (3, 3),
+            min_gl_version: (3, 3),
+            profile: GlProfile::Core,
+            samples: None,
+            depth_bits: Some(24),
+            stencil_bits: Some(8),
+            is_srgb: false,
+            swap_interval: None,
+            is_resizable: true,
+            is_decorated: true,
+            is_floating: false,
+            mode: WindowMode::Windowed,
+            position: None,
+        }
+    }
+
+    /// Requested context version, lower versions down to the minimum are
+    /// tried when the driver does not support it
+    pub fn set_gl_version(&mut self, major: u32, minor: u32) {
+        self.gl_version = (major, minor);
+    }
+
+    /// Lowest acceptable context version, 3.3 by default because the built-in
+    /// shaders use `#version 330`
+    pub fn set_min_gl_version(&mut self, major: u32, minor: u32) {
+        self.min_gl_version = (major, minor);
+    }
+
+    pub fn set_profile(&mut self, profile: GlProfile) {
+        self.profile = profile;
+    }
+
+    /// Number of MSAA samples, `None` or 0 disables multisampling
+    pub fn set_samples(&mut self, samples: Option<u32>) {
+        self.samples = samples;
+    }
+
+    pub fn set_depth_bits(&mut self, bits: Option<u32>) {
+        self.depth_bits = bits;
+    }
+
+    pub fn set_stencil_bits(&mut self, bits: Option<u32>) {
+        self.stencil_bits = bits;
+    }
+
+    /// Requests an sRGB capable framebuffer and enables `GL_FRAMEBUFFER_SRGB`
+    /// once the functions are loaded
+    pub fn set_srgb(&mut self, is_srgb: bool) {
+        self.is_srgb = is_srgb;
+    }
+
+    /// Number of screen refreshes to wait for on every buffer swap, applied
+    /// by `Window::set_current`
+    pub fn set_swap_interval(&mut self, interval: u32) {
+        self.swap_interval = Some(interval);
+    }
+
+    pub fn set_vsync(&mut self, is_enabled: bool) {
+        self.set_swap_interval(u32::from(is_enabled));
+    }
+
+    pub fn set_resizable(&mut self, is_resizable: bool) {
+        self.is_resizable = is_resizable;
+    }
+
+    pub fn set_decorated(&mut self, is_decorated: bool) {
+        self.is_decorated = is_decorated;
+    }
+
+    /// Keeps the window above all others
+    pub fn set_floating(&mut self, is_floating: bool) {
+        self.is_floating = is_floating;
+    }
+
+    pub fn set_mode(&mut self, mode: WindowMode) {
+        self.mode = mode;
+    }
+
+    /// Position of the top-left corner in screen coordinates, ignored by
+    /// full screen modes
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.position = Some((x, y));
+    }
+
+    /// Creates the window with the newest supported version between the
+    /// requested and the minimum one.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` listing the tried versions if no context could be created
+    pub fn build(&self, glfw: &mut Glfw) -> Result<(Window, WindowEvents), Error> {
+        let versions = self.candidate_versions();
+        let mut created = None;
+        for version in &versions {
+            self.apply_hints(glfw, *version);
+            created = self.create(&mut glfw.glfw);
+            if created.is_some() {
+                break;
+            }
+        }
+        glfw.apply_default_hints();
+
+        let (mut window, events) = created.ok_or_else(|| {
+            let tried: Vec<String> = versions
+                .iter()
+                .map(|(major, minor)| format!("{major}.{minor}"))
+                .collect();
+            Error::WindowCreation(format!(
+                "no OpenGL {:?} context could be created, tried versions: {}",
+                self.profile,
+                if tried.is_empty() {
+                    String::from("none")
+                } else {
+                    tried.join(", ")
+                }
+            ))
+        })?;
+
+        if let (WindowMode::Windowed, Some((x, y))) = (self.mode, self.position) {
+            window.set_pos(x, y);
+        }
+        let (width, height) = window.get_framebuffer_size();
+        let resolution = Rc::new(Resolution {
+            width: u32::try_from(width).unwrap_or(self.width),
+            height: u32::try_from(height).unwrap_or(self.height),
+        });
+
+        let (mut result, events) = glfw.wrap_window(window, events, resolution);
+        result.is_srgb = self.is_srgb;
+        result.swap_interval = self.swap_interval;
+        Ok((result, events))
+    }
+
+    fn candidate_versions(&self) -> Vec<(u32, u32)> {
+        let mut result: Vec<(u32, u32)> = WindowBuilder::GL_VERSIONS
+            .iter()
+            .copied()
+            .filter(|version| *version <= self.gl_version && *version >= self.min_gl_version)
+            .collect();
+        if self.gl_version >= self.min_gl_version && !result.contains(&self.gl_version) {
+            result.insert(0, self.gl_version);
+        }
+        result
+    }
+
+    fn apply_hints(&self, glfw: &mut Glfw, version: (u32, u32)) {
+        glfw.glfw.default_window_hints();
+        glfw.apply_invisible_hints();
+
+        let hints = [
+            glfw::WindowHint::ContextVersion(version.0, version.1),
+            // profiles only exist since OpenGL 3.2
+            glfw::WindowHint::OpenGlProfile(match self.profile {
+                _ if version < (3, 2) => glfw::OpenGlProfileHint::Any,
+                GlProfile::Core => glfw::OpenGlProfileHint::Core,
+                GlProfile::Compatibility => glfw::OpenGlProfileHint::Compat,
+                GlProfile::Any => glfw::OpenGlProfileHint::Any,
+            }),
+            glfw::WindowHint::OpenGlForwardCompat(self.profile == GlProfile::Core),
+            glfw::WindowHint::Samples(self.samples.filter(|samples| *samples > 0)),
+            glfw::WindowHint::DepthBits(self.depth_bits),
+            glfw::WindowHint::StencilBits(self.stencil_bits),
+            glfw::WindowHint::SRgbCapable(self.is_srgb),
+            glfw::WindowHint::Resizable(self.is_resizable),
+            glfw::WindowHint::Decorated(self.is_decorated && self.mode != WindowMode::Borderless),
+            glfw::WindowHint::Floating(self.is_floating),
+        ];
+        for hint in hints {
+            glfw.glfw.window_hint(hint);
+        }
+    }
+
+    fn create(
+        &self,
+        glfw: &mut glfw::Glfw,
+    ) -> Option<(glfw::Window, Receiver<(f64, glfw::WindowEvent)>)> {
+        let title = self.title.as_str();
+        match self.mode {
+            WindowMode::Windowed => {
+                glfw.create_window(self.width, self.height, title, glfw::WindowMode::Windowed)
+            }
+            WindowMode::Fullscreen => glfw.with_primary_monitor(|glfw, monitor| {
+                let mode = monitor.map_or(glfw::WindowMode::Windowed, glfw::WindowMode::FullScreen);
+                glfw.create_window(self.width, self.height, title, mode)
+            }),
+            WindowMode::Borderless => glfw.with_primary_monitor(|glfw, monitor| {
+                let Some((monitor, video_mode)) = monitor
+                    .and_then(|monitor| monitor.get_video_mode().map(|mode| (monitor, mode)))
+                else {
+                    return glfw.create_window(
+                        self.width,
+                        self.height,
+                        title,
+                        glfw::WindowMode::Windowed,
+                    );
+                };
+                glfw.window_hint(glfw::WindowHint::RedBits(Some(video_mode.red_bits)));
+                glfw.window_hint(glfw::WindowHint::GreenBits(Some(video_mode.green_bits)));
+                glfw.window_hint(glfw::WindowHint::BlueBits(Some(video_mode.blue_bits)));
+                glfw.window_hint(glfw::WindowHint::RefreshRate(Some(video_mode.refresh_rate)));
+                glfw.create_window(
+                    video_mode.width,
+                    video_mode.height,
+                    title,
+                    glfw::WindowMode::FullScreen(monitor),
+                )
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_builder_candidate_versions() {
+        let mut builder = WindowBuilder::new("test", 800, 600);
+        assert_eq!(vec![(3, 3)], builder.candidate_versions());
+
+        builder.set_gl_version(4, 3);
+        assert_eq!(
+            vec![(4, 3), (4, 2), (4, 1), (4, 0), (3, 3)],
+            builder.candidate_versions()
+        );
+
+        builder.set_min_gl_version(4, 1);
+        assert_eq!(vec![(4, 3), (4, 2), (4, 1)], builder.candidate_versions());
+    }
+
+    #[test]
+    fn test_window_builder_legacy_version() {
+        let mut builder = WindowBuilder::new("test", 800, 600);
+        builder.set_profile(GlProfile::Compatibility);
+        builder.set_gl_version(2, 1);
+        assert!(builder.candidate_versions().is_empty());
+
+        builder.set_min_gl_version(2, 0);
+        assert_eq!(vec![(2, 1)], builder.candidate_versions());
+    }
+}
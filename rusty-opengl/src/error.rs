@@ -0,0 +1,97 @@
+use crate::shaders::diagnostic::ShaderDiagnostic;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    ShaderCompilation(ShaderDiagnostic),
+    ShaderLink(ShaderDiagnostic),
+    ShaderSourceNotFound(PathBuf),
+    ShaderPreprocess(String),
+    InvalidProgram(String),
+    UniformNotFound(String),
+    InvalidVertexLayout(String),
+    TextureNotFound(PathBuf),
+    InvalidTextureData(String),
+    AtlasPacking(String),
+    InvalidAtlasLayout(String),
+    ImageDecode(String),
+    ImageEncode(String),
+    WindowCreation(String),
+    UnsupportedFeature(String),
+    IncompleteFramebuffer(String),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ShaderCompilation(diagnostic) | Error::ShaderLink(diagnostic) => {
+                write!(f, "{diagnostic}")
+            }
+            Error::ShaderSourceNotFound(path) => {
+                write!(
+                    f,
+                    "Shader source could not be opened, path: {}",
+                    path.display()
+                )
+            }
+            Error::ShaderPreprocess(reason) => write!(f, "Shader preprocessing failed: {reason}"),
+            Error::InvalidProgram(reason) => write!(f, "Invalid shader program: {reason}"),
+            Error::UniformNotFound(name) => write!(f, "Active uniform not found: {name}"),
+            Error::InvalidVertexLayout(reason) => write!(f, "Invalid vertices structure: {reason}"),
+            Error::TextureNotFound(path) => {
+                write!(f, "Failed to load texture at path {}", path.display())
+            }
+            Error::InvalidTextureData(reason) => write!(f, "Invalid texture data: {reason}"),
+            Error::AtlasPacking(reason) => write!(f, "Failed to pack texture atlas: {reason}"),
+            Error::InvalidAtlasLayout(reason) => {
+                write!(f, "Invalid texture atlas layout: {reason}")
+            }
+            Error::ImageDecode(reason) => write!(f, "Failed to decode image: {reason}"),
+            Error::ImageEncode(reason) => write!(f, "Failed to save image: {reason}"),
+            Error::WindowCreation(reason) => write!(f, "Failed to create window: {reason}"),
+            Error::UnsupportedFeature(reason) => {
+                write!(f, "Not supported by the OpenGL context: {reason}")
+            }
+            Error::IncompleteFramebuffer(reason) => {
+                write!(f, "Framebuffer is incomplete: {reason}")
+            }
+            Error::Io(error) => write!(f, "I/O error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_contains_info_log() {
+        let error = Error::ShaderLink(ShaderDiagnostic::link("error: syntax error"));
+        assert!(error.to_string().contains("error: syntax error"));
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let error = Error::from(io_error);
+        assert!(matches!(error, Error::Io(_)));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
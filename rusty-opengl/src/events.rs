@@ -0,0 +1,321 @@
+pub mod input;
+
+use std::path::PathBuf;
+
+macro_rules! keys {
+    ($($name:ident),+ $(,)?) => {
+        /// Keyboard key, named after its position on a US layout
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $($name,)+
+        }
+
+        impl From<glfw::Key> for Key {
+            fn from(key: glfw::Key) -> Self {
+                match key {
+                    $(glfw::Key::$name => Key::$name,)+
+                }
+            }
+        }
+    };
+}
+
+keys!(
+    Space,
+    Apostrophe,
+    Comma,
+    Minus,
+    Period,
+    Slash,
+    Num0,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    Semicolon,
+    Equal,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    LeftBracket,
+    Backslash,
+    RightBracket,
+    GraveAccent,
+    World1,
+    World2,
+    Escape,
+    Enter,
+    Tab,
+    Backspace,
+    Insert,
+    Delete,
+    Right,
+    Left,
+    Down,
+    Up,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    F25,
+    Kp0,
+    Kp1,
+    Kp2,
+    Kp3,
+    Kp4,
+    Kp5,
+    Kp6,
+    Kp7,
+    Kp8,
+    Kp9,
+    KpDecimal,
+    KpDivide,
+    KpMultiply,
+    KpSubtract,
+    KpAdd,
+    KpEnter,
+    KpEqual,
+    LeftShift,
+    LeftControl,
+    LeftAlt,
+    LeftSuper,
+    RightShift,
+    RightControl,
+    RightAlt,
+    RightSuper,
+    Menu,
+    Unknown,
+);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// Extra buttons, numbered from 4 like in GLFW
+    Other(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Press,
+    Release,
+    Repeat,
+}
+
+/// Modifier keys held while a key or a mouse button changed state
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub super_key: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Key {
+        key: Key,
+        scancode: i32,
+        action: Action,
+        modifiers: Modifiers,
+    },
+    MouseButton {
+        button: MouseButton,
+        action: Action,
+        modifiers: Modifiers,
+    },
+    /// Cursor position in screen coordinates, origin in the top-left corner
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    Scroll {
+        x: f64,
+        y: f64,
+    },
+    /// New framebuffer size in pixels
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    Focused(bool),
+    CloseRequested,
+    FileDropped(Vec<PathBuf>),
+}
+
+impl Event {
+    /// Converts a GLFW event, returns `None` for events the crate does not expose
+    #[must_use]
+    pub fn from_glfw(event: glfw::WindowEvent) -> Option<Self> {
+        let result = match event {
+            glfw::WindowEvent::Key(key, scancode, action, modifiers) => Event::Key {
+                key: Key::from(key),
+                scancode,
+                action: Action::from(action),
+                modifiers: Modifiers::from(modifiers),
+            },
+            glfw::WindowEvent::MouseButton(button, action, modifiers) => Event::MouseButton {
+                button: MouseButton::from(button),
+                action: Action::from(action),
+                modifiers: Modifiers::from(modifiers),
+            },
+            glfw::WindowEvent::CursorPos(x, y) => Event::CursorMoved { x, y },
+            glfw::WindowEvent::Scroll(x, y) => Event::Scroll { x, y },
+            glfw::WindowEvent::FramebufferSize(width, height) => Event::Resized {
+                width: u32::try_from(width).unwrap_or(0),
+                height: u32::try_from(height).unwrap_or(0),
+            },
+            glfw::WindowEvent::Focus(is_focused) => Event::Focused(is_focused),
+            glfw::WindowEvent::Close => Event::CloseRequested,
+            glfw::WindowEvent::FileDrop(paths) => Event::FileDropped(paths),
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+impl From<glfw::MouseButton> for MouseButton {
+    fn from(button: glfw::MouseButton) -> Self {
+        match button {
+            glfw::MouseButton::Button1 => MouseButton::Left,
+            glfw::MouseButton::Button2 => MouseButton::Right,
+            glfw::MouseButton::Button3 => MouseButton::Middle,
+            glfw::MouseButton::Button4 => MouseButton::Other(4),
+            glfw::MouseButton::Button5 => MouseButton::Other(5),
+            glfw::MouseButton::Button6 => MouseButton::Other(6),
+            glfw::MouseButton::Button7 => MouseButton::Other(7),
+            glfw::MouseButton::Button8 => MouseButton::Other(8),
+        }
+    }
+}
+
+impl From<glfw::Action> for Action {
+    fn from(action: glfw::Action) -> Self {
+        match action {
+            glfw::Action::Press => Action::Press,
+            glfw::Action::Release => Action::Release,
+            glfw::Action::Repeat => Action::Repeat,
+        }
+    }
+}
+
+impl From<glfw::Modifiers> for Modifiers {
+    fn from(modifiers: glfw::Modifiers) -> Self {
+        Modifiers {
+            shift: modifiers.contains(glfw::Modifiers::Shift),
+            control: modifiers.contains(glfw::Modifiers::Control),
+            alt: modifiers.contains(glfw::Modifiers::Alt),
+            super_key: modifiers.contains(glfw::Modifiers::Super),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WindowEvents;
+
+    #[test]
+    fn test_event_from_glfw_key() {
+        let event = Event::from_glfw(glfw::WindowEvent::Key(
+            glfw::Key::Escape,
+            9,
+            glfw::Action::Press,
+            glfw::Modifiers::Shift | glfw::Modifiers::Control,
+        ));
+        let expected = Event::Key {
+            key: Key::Escape,
+            scancode: 9,
+            action: Action::Press,
+            modifiers: Modifiers {
+                shift: true,
+                control: true,
+                ..Modifiers::default()
+            },
+        };
+        assert_eq!(Some(expected), event);
+    }
+
+    #[test]
+    fn test_event_from_glfw_resize_and_ignored() {
+        let event = Event::from_glfw(glfw::WindowEvent::FramebufferSize(640, 480));
+        assert_eq!(
+            Some(Event::Resized {
+                width: 640,
+                height: 480
+            }),
+            event
+        );
+        assert_eq!(None, Event::from_glfw(glfw::WindowEvent::Refresh));
+    }
+
+    #[test]
+    fn test_window_events_injected() {
+        let mut events = WindowEvents::empty();
+        events.inject(Event::Focused(false));
+        events.inject(Event::CloseRequested);
+        assert_eq!(
+            vec![Event::Focused(false), Event::CloseRequested],
+            events.poll()
+        );
+        assert!(events.poll().is_empty());
+    }
+}
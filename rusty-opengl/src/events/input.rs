@@ -0,0 +1,211 @@
+use crate::events::{Action, Event, Key, MouseButton};
+use crate::math::vector::Vec2;
+use std::collections::HashSet;
+
+/// Keyboard and mouse state built from events.
+///
+/// Call `begin_frame` once per frame before handling the new events, the
+/// `just_*` queries then describe changes since the previous frame.
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    keys_down: HashSet<Key>,
+    keys_pressed: HashSet<Key>,
+    keys_released: HashSet<Key>,
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    mouse_position: Vec2,
+    scroll: Vec2,
+    is_focused: bool,
+    is_close_requested: bool,
+}
+
+impl InputState {
+    #[must_use]
+    pub fn new() -> Self {
+        InputState {
+            is_focused: true,
+            ..InputState::default()
+        }
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.scroll = Vec2::ZERO;
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Key { key, action, .. } => match action {
+                Action::Press => {
+                    if self.keys_down.insert(*key) {
+                        self.keys_pressed.insert(*key);
+                    }
+                }
+                Action::Release => {
+                    if self.keys_down.remove(key) {
+                        self.keys_released.insert(*key);
+                    }
+                }
+                Action::Repeat => {}
+            },
+            Event::MouseButton { button, action, .. } => match action {
+                Action::Press => {
+                    if self.buttons_down.insert(*button) {
+                        self.buttons_pressed.insert(*button);
+                    }
+                }
+                Action::Release => {
+                    if self.buttons_down.remove(button) {
+                        self.buttons_released.insert(*button);
+                    }
+                }
+                Action::Repeat => {}
+            },
+            Event::CursorMoved { x, y } => {
+                self.mouse_position = Vec2::new(*x as f32, *y as f32);
+            }
+            Event::Scroll { x, y } => self.scroll += Vec2::new(*x as f32, *y as f32),
+            Event::Focused(is_focused) => {
+                self.is_focused = *is_focused;
+                if !is_focused {
+                    self.release_all();
+                }
+            }
+            Event::CloseRequested => self.is_close_requested = true,
+            Event::Resized { .. } | Event::FileDropped(_) => {}
+        }
+    }
+
+    pub fn handle_events<'a>(&mut self, events: impl IntoIterator<Item = &'a Event>) {
+        for event in events {
+            self.handle_event(event);
+        }
+    }
+
+    #[must_use]
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    #[must_use]
+    pub fn is_key_just_pressed(&self, key: Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    #[must_use]
+    pub fn is_key_just_released(&self, key: Key) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    #[must_use]
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    #[must_use]
+    pub fn is_button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    #[must_use]
+    pub fn is_button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Cursor position in screen coordinates, see `Camera2D::screen_to_world`
+    #[must_use]
+    pub fn get_mouse_position(&self) -> Vec2 {
+        self.mouse_position
+    }
+
+    /// Scroll offset accumulated during the current frame
+    #[must_use]
+    pub fn get_scroll(&self) -> Vec2 {
+        self.scroll
+    }
+
+    #[must_use]
+    pub fn is_focused(&self) -> bool {
+        self.is_focused
+    }
+
+    #[must_use]
+    pub fn is_close_requested(&self) -> bool {
+        self.is_close_requested
+    }
+
+    /// Release events are not delivered to unfocused windows
+    fn release_all(&mut self) {
+        self.keys_released.extend(self.keys_down.drain());
+        self.buttons_released.extend(self.buttons_down.drain());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Modifiers;
+
+    fn key_event(key: Key, action: Action) -> Event {
+        Event::Key {
+            key,
+            scancode: 0,
+            action,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[test]
+    fn test_input_key_press_and_release() {
+        let mut input = InputState::new();
+        input.handle_event(&key_event(Key::W, Action::Press));
+        assert!(input.is_key_down(Key::W));
+        assert!(input.is_key_just_pressed(Key::W));
+
+        input.begin_frame();
+        input.handle_event(&key_event(Key::W, Action::Repeat));
+        assert!(input.is_key_down(Key::W));
+        assert!(!input.is_key_just_pressed(Key::W));
+
+        input.begin_frame();
+        input.handle_event(&key_event(Key::W, Action::Release));
+        assert!(!input.is_key_down(Key::W));
+        assert!(input.is_key_just_released(Key::W));
+    }
+
+    #[test]
+    fn test_input_mouse_and_scroll() {
+        let mut input = InputState::new();
+        input.handle_events(&[
+            Event::CursorMoved { x: 10.0, y: 20.0 },
+            Event::MouseButton {
+                button: MouseButton::Left,
+                action: Action::Press,
+                modifiers: Modifiers::default(),
+            },
+            Event::Scroll { x: 0.0, y: 1.0 },
+            Event::Scroll { x: 0.0, y: 2.0 },
+        ]);
+        assert_eq!(Vec2::new(10.0, 20.0), input.get_mouse_position());
+        assert!(input.is_button_just_pressed(MouseButton::Left));
+        assert_eq!(Vec2::new(0.0, 3.0), input.get_scroll());
+
+        input.begin_frame();
+        assert!(input.is_button_down(MouseButton::Left));
+        assert_eq!(Vec2::ZERO, input.get_scroll());
+    }
+
+    #[test]
+    fn test_input_focus_loss_releases_keys() {
+        let mut input = InputState::new();
+        input.handle_event(&key_event(Key::Space, Action::Press));
+        input.handle_event(&Event::Focused(false));
+        assert!(!input.is_focused());
+        assert!(!input.is_key_down(Key::Space));
+        assert!(input.is_key_just_released(Key::Space));
+    }
+}
@@ -0,0 +1,154 @@
+//! OpenGL state mirrored on the Rust side, so code that changes it only for a
+//! while can restore it without querying the context. Every context has its
+//! own copy, `Window::set_current` selects it. State changed with raw `gl`
+//! calls is not seen here, use the functions of this module instead.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Capabilities toggled with `set_depth_test` and `set_blending`, both are
+/// disabled in a new context
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub depth_test: bool,
+    pub blend: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ContextState {
+    pub(crate) capabilities: Capabilities,
+    pub(crate) framebuffer: u32,
+    pub(crate) viewport: [i32; 4],
+    pub(crate) program: u32,
+}
+
+thread_local! {
+    /// Handle of the current context, 0 before any window was made current
+    static CURRENT_CONTEXT: Cell<usize> = const { Cell::new(0) };
+    static CONTEXT_STATES: RefCell<HashMap<usize, ContextState>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_depth_test(is_enabled: bool) {
+    set_capabilities(Capabilities {
+        depth_test: is_enabled,
+        ..get_capabilities()
+    });
+}
+
+pub fn set_blending(is_enabled: bool) {
+    set_capabilities(Capabilities {
+        blend: is_enabled,
+        ..get_capabilities()
+    });
+}
+
+#[must_use]
+pub fn get_capabilities() -> Capabilities {
+    with_context_state(|state| state.capabilities)
+}
+
+/// Enables or disables only the capabilities that differ from the current ones
+pub fn set_capabilities(capabilities: Capabilities) {
+    let previous =
+        with_context_state(|state| std::mem::replace(&mut state.capabilities, capabilities));
+    let changes = [
+        (gl::DEPTH_TEST, previous.depth_test, capabilities.depth_test),
+        (gl::BLEND, previous.blend, capabilities.blend),
+    ];
+    for (capability, was_enabled, is_enabled) in changes {
+        if was_enabled != is_enabled {
+            unsafe {
+                if is_enabled {
+                    gl::Enable(capability);
+                } else {
+                    gl::Disable(capability);
+                }
+            }
+        }
+    }
+}
+
+/// Binds `framebuffer` for drawing and reading, 0 is the window
+pub fn bind_framebuffer(framebuffer: u32) {
+    with_context_state(|state| state.framebuffer = framebuffer);
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+    }
+}
+
+#[must_use]
+pub fn get_framebuffer() -> u32 {
+    with_context_state(|state| state.framebuffer)
+}
+
+/// `[x, y, width, height]`
+pub fn set_viewport(viewport: [i32; 4]) {
+    with_context_state(|state| state.viewport = viewport);
+    let [x, y, width, height] = viewport;
+    unsafe {
+        gl::Viewport(x, y, width, height);
+    }
+}
+
+#[must_use]
+pub fn get_viewport() -> [i32; 4] {
+    with_context_state(|state| state.viewport)
+}
+
+/// Binds `program`, only `ShaderProgram` and `ComputeProgram` call it so
+/// uniform uploads know the active program without querying the context
+pub(crate) fn use_program(program: u32) {
+    with_context_state(|state| state.program = program);
+    unsafe {
+        gl::UseProgram(program);
+    }
+}
+
+pub(crate) fn get_program() -> u32 {
+    with_context_state(|state| state.program)
+}
+
+/// Selects the mirrored state of `context`, called when it becomes current
+pub(crate) fn make_current(context: usize) {
+    CURRENT_CONTEXT.set(context);
+}
+
+/// Starts from the defaults for a new context, its handle may belong to a
+/// destroyed one. OpenGL sets the first viewport to the size of the window.
+pub(crate) fn reset_context(context: usize, viewport: [i32; 4]) {
+    let state = ContextState {
+        viewport,
+        ..ContextState::default()
+    };
+    CONTEXT_STATES.with(|states| states.borrow_mut().insert(context, state));
+}
+
+pub(crate) fn with_context_state<R>(f: impl FnOnce(&mut ContextState) -> R) -> R {
+    let context = CURRENT_CONTEXT.get();
+    CONTEXT_STATES.with(|states| f(states.borrow_mut().entry(context).or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_states_are_separate() {
+        make_current(1);
+        with_context_state(|state| {
+            state.capabilities.blend = true;
+            state.program = 3;
+        });
+        make_current(2);
+        assert_eq!(Capabilities::default(), get_capabilities());
+        assert_eq!(0, get_program());
+
+        make_current(1);
+        assert!(get_capabilities().blend);
+        assert_eq!(3, get_program());
+        reset_context(1, [0, 0, 800, 600]);
+        assert_eq!(Capabilities::default(), get_capabilities());
+        assert_eq!([0, 0, 800, 600], get_viewport());
+        make_current(0);
+    }
+}
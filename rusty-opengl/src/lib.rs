@@ -1,5 +1,14 @@
+pub mod app;
+pub mod camera;
 pub mod circle;
 pub mod color;
 pub mod config;
+pub mod error;
+pub mod events;
+pub mod gl_state;
+pub mod math;
 pub mod polygons;
+pub mod post_process;
+pub mod readback;
+pub mod render_target;
 pub mod shaders;
@@ -0,0 +1,4 @@
+pub mod matrix;
+pub mod quaternion;
+pub mod transform;
+pub mod vector;
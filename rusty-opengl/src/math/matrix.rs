@@ -0,0 +1,535 @@
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::{Vec2, Vec3, Vec4};
+use std::ops::Mul;
+
+/// Column-major 3x3 matrix, `cols[i]` is the i-th column
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mat3 {
+    pub cols: [[f32; 3]; 3],
+}
+
+/// Column-major 4x4 matrix, `cols[i]` is the i-th column
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mat4 {
+    pub cols: [[f32; 4]; 4],
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3 {
+        cols: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    #[must_use]
+    pub const fn from_cols(cols: [[f32; 3]; 3]) -> Self {
+        Mat3 { cols }
+    }
+
+    /// 2D translation in homogeneous coordinates
+    #[must_use]
+    pub fn from_translation(translation: Vec2) -> Self {
+        let mut result = Mat3::IDENTITY;
+        result.cols[2] = [translation.x, translation.y, 1.0];
+        result
+    }
+
+    /// 2D counter-clockwise rotation in radians
+    #[must_use]
+    pub fn from_rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Mat3::from_cols([[cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    #[must_use]
+    pub fn from_scale(scale: Vec2) -> Self {
+        Mat3::from_cols([[scale.x, 0.0, 0.0], [0.0, scale.y, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut result = Mat3::IDENTITY;
+        for (col, values) in self.cols.iter().enumerate() {
+            for (row, value) in values.iter().enumerate() {
+                result.cols[row][col] = *value;
+            }
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn determinant(&self) -> f32 {
+        let [a, b, c] = self.cols;
+        a[0] * (b[1] * c[2] - c[1] * b[2]) - b[0] * (a[1] * c[2] - c[1] * a[2])
+            + c[0] * (a[1] * b[2] - b[1] * a[2])
+    }
+
+    /// Returns `None` for singular matrices
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if is_singular(determinant, &self.cols) {
+            return None;
+        }
+
+        let [a, b, c] = self.cols.map(|col| Vec3::new(col[0], col[1], col[2]));
+        let rows = [b.cross(c), c.cross(a), a.cross(b)].map(|row| row / determinant);
+        let result = Mat3::from_cols([rows[0].to_array(), rows[1].to_array(), rows[2].to_array()])
+            .transpose();
+        result
+            .cols
+            .iter()
+            .flatten()
+            .all(|value| value.is_finite())
+            .then_some(result)
+    }
+
+    /// Transforms a 2D point, including translation
+    #[must_use]
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        (*self * point.extend(1.0)).truncate()
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        cols: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    #[must_use]
+    pub const fn from_cols(cols: [[f32; 4]; 4]) -> Self {
+        Mat4 { cols }
+    }
+
+    #[must_use]
+    pub fn from_translation(translation: Vec3) -> Self {
+        let mut result = Mat4::IDENTITY;
+        result.cols[3] = [translation.x, translation.y, translation.z, 1.0];
+        result
+    }
+
+    #[must_use]
+    pub fn from_scale(scale: Vec3) -> Self {
+        let mut result = Mat4::IDENTITY;
+        result.cols[0][0] = scale.x;
+        result.cols[1][1] = scale.y;
+        result.cols[2][2] = scale.z;
+        result
+    }
+
+    #[must_use]
+    pub fn from_quaternion(rotation: Quaternion) -> Self {
+        let Quaternion { x, y, z, w } = rotation.normalize();
+        Mat4::from_cols([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y + w * z),
+                2.0 * (x * z - w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y - w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z + w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z + w * y),
+                2.0 * (y * z - w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation in radians around the z axis
+    #[must_use]
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Mat4::from_quaternion(Quaternion::from_axis_angle(Vec3::Z, angle))
+    }
+
+    /// Orthographic projection matching `glOrtho`
+    #[must_use]
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let width = right - left;
+        let height = top - bottom;
+        let depth = far - near;
+        Mat4::from_cols([
+            [2.0 / width, 0.0, 0.0, 0.0],
+            [0.0, 2.0 / height, 0.0, 0.0],
+            [0.0, 0.0, -2.0 / depth, 0.0],
+            [
+                -(right + left) / width,
+                -(top + bottom) / height,
+                -(far + near) / depth,
+                1.0,
+            ],
+        ])
+    }
+
+    /// Right-handed perspective projection matching `gluPerspective`,
+    /// `fov_y` is in radians
+    #[must_use]
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let focal = 1.0 / (fov_y / 2.0).tan();
+        let depth = near - far;
+        Mat4::from_cols([
+            [focal / aspect_ratio, 0.0, 0.0, 0.0],
+            [0.0, focal, 0.0, 0.0],
+            [0.0, 0.0, (far + near) / depth, -1.0],
+            [0.0, 0.0, 2.0 * far * near / depth, 0.0],
+        ])
+    }
+
+    /// Right-handed view matrix matching `gluLookAt`
+    #[must_use]
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = (target - eye).normalize();
+        let side = forward.cross(up).normalize();
+        let up = side.cross(forward);
+        Mat4::from_cols([
+            [side.x, up.x, -forward.x, 0.0],
+            [side.y, up.y, -forward.y, 0.0],
+            [side.z, up.z, -forward.z, 0.0],
+            [-side.dot(eye), -up.dot(eye), forward.dot(eye), 1.0],
+        ])
+    }
+
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut result = Mat4::IDENTITY;
+        for (col, values) in self.cols.iter().enumerate() {
+            for (row, value) in values.iter().enumerate() {
+                result.cols[row][col] = *value;
+            }
+        }
+        result
+    }
+
+    /// Returns `None` for singular matrices
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let m: [f32; 16] = std::array::from_fn(|index| self.cols[index / 4][index % 4]);
+        let mut inv = [0_f32; 16];
+
+        inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+            + m[9] * m[7] * m[14]
+            + m[13] * m[6] * m[11]
+            - m[13] * m[7] * m[10];
+        inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+            - m[8] * m[7] * m[14]
+            - m[12] * m[6] * m[11]
+            + m[12] * m[7] * m[10];
+        inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+            + m[8] * m[7] * m[13]
+            + m[12] * m[5] * m[11]
+            - m[12] * m[7] * m[9];
+        inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+            - m[8] * m[6] * m[13]
+            - m[12] * m[5] * m[10]
+            + m[12] * m[6] * m[9];
+        inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+            - m[9] * m[3] * m[14]
+            - m[13] * m[2] * m[11]
+            + m[13] * m[3] * m[10];
+        inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+            + m[8] * m[3] * m[14]
+            + m[12] * m[2] * m[11]
+            - m[12] * m[3] * m[10];
+        inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+            - m[8] * m[3] * m[13]
+            - m[12] * m[1] * m[11]
+            + m[12] * m[3] * m[9];
+        inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+            + m[8] * m[2] * m[13]
+            + m[12] * m[1] * m[10]
+            - m[12] * m[2] * m[9];
+        inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+            + m[5] * m[3] * m[14]
+            + m[13] * m[2] * m[7]
+            - m[13] * m[3] * m[6];
+        inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+            - m[4] * m[3] * m[14]
+            - m[12] * m[2] * m[7]
+            + m[12] * m[3] * m[6];
+        inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+            + m[4] * m[3] * m[13]
+            + m[12] * m[1] * m[7]
+            - m[12] * m[3] * m[5];
+        inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+            - m[4] * m[2] * m[13]
+            - m[12] * m[1] * m[6]
+            + m[12] * m[2] * m[5];
+        inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+            - m[5] * m[3] * m[10]
+            - m[9] * m[2] * m[7]
+            + m[9] * m[3] * m[6];
+        inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+            + m[4] * m[3] * m[10]
+            + m[8] * m[2] * m[7]
+            - m[8] * m[3] * m[6];
+        inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+            - m[4] * m[3] * m[9]
+            - m[8] * m[1] * m[7]
+            + m[8] * m[3] * m[5];
+        inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+            + m[4] * m[2] * m[9]
+            + m[8] * m[1] * m[6]
+            - m[8] * m[2] * m[5];
+
+        let determinant = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+        if is_singular(determinant, &self.cols) {
+            return None;
+        }
+
+        let mut result = Mat4::IDENTITY;
+        for (index, value) in inv.iter().enumerate() {
+            let value = value / determinant;
+            if !value.is_finite() {
+                return None;
+            }
+            result.cols[index / 4][index % 4] = value;
+        }
+        Some(result)
+    }
+
+    #[must_use]
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let result = *self * point.extend(1.0);
+        // the terms summed into w bound it, so the threshold scales with them
+        let magnitude: f32 = point
+            .extend(1.0)
+            .to_array()
+            .iter()
+            .zip(&self.cols)
+            .map(|(value, col)| (value * col[3]).abs())
+            .sum();
+        if !result.w.is_finite() || result.w.abs() <= magnitude * f32::EPSILON {
+            return result.truncate();
+        }
+        result.truncate() / result.w
+    }
+
+    /// Transforms a direction, ignoring translation
+    #[must_use]
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        (*self * vector.extend(0.0)).truncate()
+    }
+}
+
+/// The product of the column lengths bounds the magnitude of the determinant,
+/// so the threshold scales with the matrix instead of being absolute
+fn is_singular<const N: usize>(determinant: f32, cols: &[[f32; N]; N]) -> bool {
+    let magnitude: f32 = cols
+        .iter()
+        .map(|col| col.iter().map(|value| value * value).sum::<f32>().sqrt())
+        .product();
+    !determinant.is_finite() || determinant.abs() <= magnitude * f32::EPSILON
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Mat3::IDENTITY
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Mat4::IDENTITY
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, other: Mat3) -> Mat3 {
+        let mut result = Mat3::from_cols([[0.0; 3]; 3]);
+        for (col, values) in other.cols.iter().enumerate() {
+            let product = self * Vec3::new(values[0], values[1], values[2]);
+            result.cols[col] = product.to_array();
+        }
+        result
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, vector: Vec3) -> Vec3 {
+        let [a, b, c] = self.cols;
+        Vec3::new(
+            a[0] * vector.x + b[0] * vector.y + c[0] * vector.z,
+            a[1] * vector.x + b[1] * vector.y + c[1] * vector.z,
+            a[2] * vector.x + b[2] * vector.y + c[2] * vector.z,
+        )
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, other: Mat4) -> Mat4 {
+        let mut result = Mat4::from_cols([[0.0; 4]; 4]);
+        for (col, values) in other.cols.iter().enumerate() {
+            let product = self * Vec4::new(values[0], values[1], values[2], values[3]);
+            result.cols[col] = product.to_array();
+        }
+        result
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, vector: Vec4) -> Vec4 {
+        let [a, b, c, d] = self.cols;
+        Vec4::new(
+            a[0] * vector.x + b[0] * vector.y + c[0] * vector.z + d[0] * vector.w,
+            a[1] * vector.x + b[1] * vector.y + c[1] * vector.z + d[1] * vector.w,
+            a[2] * vector.x + b[2] * vector.y + c[2] * vector.z + d[2] * vector.w,
+            a[3] * vector.x + b[3] * vector.y + c[3] * vector.z + d[3] * vector.w,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec3_eq(expected: Vec3, result: Vec3) {
+        assert!(
+            (expected - result).length() < 1e-5,
+            "expected {expected:?}, got {result:?}"
+        );
+    }
+
+    fn assert_mat4_eq(expected: Mat4, result: Mat4) {
+        for (expected, result) in expected
+            .cols
+            .iter()
+            .flatten()
+            .zip(result.cols.iter().flatten())
+        {
+            assert!(
+                (expected - result).abs() < 1e-5,
+                "expected {expected}, got {result}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mat4_translation_scale_order() {
+        let matrix = Mat4::from_translation(Vec3::new(1.0, 2.0, 0.0))
+            * Mat4::from_scale(Vec3::new(2.0, 2.0, 1.0));
+        assert_vec3_eq(
+            Vec3::new(3.0, 4.0, 0.0),
+            matrix.transform_point(Vec3::new(1.0, 1.0, 0.0)),
+        );
+        assert_vec3_eq(Vec3::new(2.0, 0.0, 0.0), matrix.transform_vector(Vec3::X));
+    }
+
+    #[test]
+    fn test_mat4_rotation_z() {
+        let matrix = Mat4::from_rotation_z(std::f32::consts::FRAC_PI_2);
+        assert_vec3_eq(Vec3::Y, matrix.transform_point(Vec3::X));
+    }
+
+    #[test]
+    fn test_mat4_inverse() {
+        let matrix = Mat4::from_translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::from_rotation_z(0.7)
+            * Mat4::from_scale(Vec3::new(2.0, 3.0, 4.0));
+        assert_mat4_eq(Mat4::IDENTITY, matrix * matrix.inverse().unwrap());
+        assert!(Mat4::from_scale(Vec3::ZERO).inverse().is_none());
+    }
+
+    #[test]
+    fn test_inverse_of_small_scale() {
+        let matrix = Mat4::from_scale(Vec3::new(0.01, 0.01, 0.01));
+        assert_mat4_eq(Mat4::IDENTITY, matrix * matrix.inverse().unwrap());
+
+        let matrix = Mat3::from_scale(Vec2::new(1e-4, 1e-4));
+        let point = matrix
+            .inverse()
+            .unwrap()
+            .transform_point(Vec2::new(1e-4, 2e-4));
+        assert!((point - Vec2::new(1.0, 2.0)).length() < 1e-4);
+
+        let rank_deficient = Mat3::from_cols([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 1.0]]);
+        assert!(rank_deficient.inverse().is_none());
+        let rank_deficient = Mat4::from_cols([
+            [1e-3, 0.0, 0.0, 0.0],
+            [2e-3, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1e-3, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert!(rank_deficient.inverse().is_none());
+    }
+
+    #[test]
+    fn test_mat4_transform_point_small_w() {
+        let matrix = Mat4::from_cols([
+            [1e-10, 0.0, 0.0, 0.0],
+            [0.0, 1e-10, 0.0, 0.0],
+            [0.0, 0.0, 1e-10, 0.0],
+            [0.0, 0.0, 0.0, 1e-10],
+        ]);
+        assert_vec3_eq(
+            Vec3::new(1.0, 2.0, 3.0),
+            matrix.transform_point(Vec3::new(1.0, 2.0, 3.0)),
+        );
+
+        let point_at_infinity = Mat4::from_cols([
+            [1.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, -1.0],
+        ]);
+        assert_vec3_eq(Vec3::X, point_at_infinity.transform_point(Vec3::X));
+    }
+
+    #[test]
+    fn test_mat4_orthographic() {
+        let matrix = Mat4::orthographic(0.0, 800.0, 0.0, 600.0, -1.0, 1.0);
+        assert_vec3_eq(
+            Vec3::new(-1.0, -1.0, 0.0),
+            matrix.transform_point(Vec3::ZERO),
+        );
+        assert_vec3_eq(
+            Vec3::new(1.0, 1.0, 0.0),
+            matrix.transform_point(Vec3::new(800.0, 600.0, 0.0)),
+        );
+    }
+
+    #[test]
+    fn test_mat4_perspective_and_look_at() {
+        let view = Mat4::look_at(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        assert_vec3_eq(Vec3::new(0.0, 0.0, -5.0), view.transform_point(Vec3::ZERO));
+
+        let projection = Mat4::perspective(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 10.0);
+        assert_vec3_eq(
+            Vec3::new(0.0, 0.0, -1.0),
+            projection.transform_point(Vec3::new(0.0, 0.0, -1.0)),
+        );
+        assert_vec3_eq(
+            Vec3::new(0.0, 0.0, 1.0),
+            projection.transform_point(Vec3::new(0.0, 0.0, -10.0)),
+        );
+    }
+
+    #[test]
+    fn test_mat3_2d_transform_and_inverse() {
+        let matrix = Mat3::from_translation(Vec2::new(1.0, 1.0))
+            * Mat3::from_rotation(std::f32::consts::PI)
+            * Mat3::from_scale(Vec2::new(2.0, 2.0));
+        let point = matrix.transform_point(Vec2::new(1.0, 0.0));
+        assert!((point - Vec2::new(-1.0, 1.0)).length() < 1e-5);
+
+        let back = matrix.inverse().unwrap().transform_point(point);
+        assert!((back - Vec2::new(1.0, 0.0)).length() < 1e-5);
+    }
+}
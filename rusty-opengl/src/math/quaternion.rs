@@ -0,0 +1,180 @@
+use crate::math::vector::Vec3;
+use std::ops::Mul;
+
+/// Rotation quaternion, `w` is the scalar part
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Quaternion = Quaternion::new(0.0, 0.0, 0.0, 1.0);
+
+    #[must_use]
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Quaternion { x, y, z, w }
+    }
+
+    /// Counter-clockwise rotation in radians around `axis`
+    #[must_use]
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Quaternion::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    /// Rotation in the xy plane, the one used by 2D shapes
+    #[must_use]
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Quaternion::from_axis_angle(Vec3::Z, angle)
+    }
+
+    /// Applies rotations around x, then y, then z, angles in radians
+    #[must_use]
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
+        Quaternion::from_axis_angle(Vec3::Z, z)
+            * Quaternion::from_axis_angle(Vec3::Y, y)
+            * Quaternion::from_axis_angle(Vec3::X, x)
+    }
+
+    #[must_use]
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    #[must_use]
+    pub fn dot(self, other: Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// Returns the identity for a zero quaternion. Divides by the largest
+    /// component first, so tiny and huge quaternions do not under- or
+    /// overflow when squared.
+    #[must_use]
+    pub fn normalize(self) -> Self {
+        let scale = [self.x, self.y, self.z, self.w]
+            .iter()
+            .fold(0.0_f32, |result, value| result.max(value.abs()));
+        if scale == 0.0 || !scale.is_finite() {
+            return Quaternion::IDENTITY;
+        }
+        let scaled = Quaternion::new(
+            self.x / scale,
+            self.y / scale,
+            self.z / scale,
+            self.w / scale,
+        );
+        let length = scaled.length();
+        Quaternion::new(
+            scaled.x / length,
+            scaled.y / length,
+            scaled.z / length,
+            scaled.w / length,
+        )
+    }
+
+    /// Inverse rotation of a unit quaternion
+    #[must_use]
+    pub fn conjugate(self) -> Self {
+        Quaternion::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    #[must_use]
+    pub fn rotate(self, vector: Vec3) -> Vec3 {
+        let axis = Vec3::new(self.x, self.y, self.z);
+        let t = axis.cross(vector) * 2.0;
+        vector + t * self.w + axis.cross(t)
+    }
+
+    /// Spherical interpolation along the shortest path, `t` in [0, 1]
+    #[must_use]
+    pub fn slerp(self, other: Quaternion, t: f32) -> Self {
+        let mut other = other;
+        let mut cos = self.dot(other);
+        if cos < 0.0 {
+            other = Quaternion::new(-other.x, -other.y, -other.z, -other.w);
+            cos = -cos;
+        }
+
+        let (from, to) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Quaternion::new(
+            self.x * from + other.x * to,
+            self.y * from + other.y * to,
+            self.z * from + other.z * to,
+            self.w * from + other.w * to,
+        )
+        .normalize()
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::IDENTITY
+    }
+}
+
+/// Combines rotations, `a * b` applies `b` first
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::matrix::Mat4;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_quaternion_rotate() {
+        let rotation = Quaternion::from_rotation_z(FRAC_PI_2);
+        assert!((rotation.rotate(Vec3::X) - Vec3::Y).length() < 1e-6);
+        assert!((rotation.conjugate().rotate(Vec3::Y) - Vec3::X).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_quaternion_matches_matrix() {
+        let rotation = Quaternion::from_euler(0.3, -1.1, 2.0);
+        let vector = Vec3::new(1.0, 2.0, 3.0);
+        let expected = Mat4::from_quaternion(rotation).transform_vector(vector);
+        assert!((rotation.rotate(vector) - expected).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_quaternion_normalize_tiny() {
+        let rotation = Quaternion::new(0.0, 0.0, 1e-30, 1e-30).normalize();
+        let expected = Quaternion::from_rotation_z(FRAC_PI_2);
+        assert!((rotation.dot(expected) - 1.0).abs() < 1e-6);
+        assert_eq!(
+            Quaternion::IDENTITY,
+            Quaternion::new(0.0, 0.0, 0.0, 0.0).normalize()
+        );
+    }
+
+    #[test]
+    fn test_quaternion_slerp() {
+        let start = Quaternion::IDENTITY;
+        let end = Quaternion::from_rotation_z(FRAC_PI_2);
+        let half = start.slerp(end, 0.5);
+        let expected = Quaternion::from_rotation_z(FRAC_PI_2 / 2.0);
+        assert!((half.dot(expected) - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::math::matrix::Mat4;
+use crate::math::quaternion::Quaternion;
+use crate::math::vector::{Vec2, Vec3};
+use crate::shaders::uniform::Uniform;
+
+/// Placement of a shape, applied as scale, then rotation, then translation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quaternion,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        translation: Vec3::ZERO,
+        rotation: Quaternion::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    #[must_use]
+    pub fn new(translation: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    #[must_use]
+    pub fn from_translation(translation: Vec2) -> Self {
+        Transform {
+            translation: translation.extend(0.0),
+            ..Transform::IDENTITY
+        }
+    }
+
+    pub fn translate(&mut self, offset: Vec2) {
+        self.translation += offset.extend(0.0);
+    }
+
+    /// Rotates counter-clockwise in the xy plane, angle in radians
+    pub fn rotate(&mut self, angle: f32) {
+        self.rotation = (Quaternion::from_rotation_z(angle) * self.rotation).normalize();
+    }
+
+    pub fn set_translation(&mut self, translation: Vec2) {
+        self.translation = translation.extend(self.translation.z);
+    }
+
+    /// Replaces the rotation by one in the xy plane, angle in radians
+    pub fn set_rotation(&mut self, angle: f32) {
+        self.rotation = Quaternion::from_rotation_z(angle);
+    }
+
+    pub fn set_scale(&mut self, scale: Vec2) {
+        self.scale = scale.extend(self.scale.z);
+    }
+
+    #[must_use]
+    pub fn get_matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * Mat4::from_quaternion(self.rotation)
+            * Mat4::from_scale(self.scale)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Uniform for Transform {
+    fn upload(&self, location: i32) {
+        self.get_matrix().upload(location);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_default_is_identity() {
+        assert_eq!(Mat4::IDENTITY, Transform::default().get_matrix());
+    }
+
+    #[test]
+    fn test_transform_order() {
+        let mut transform = Transform::from_translation(Vec2::new(0.5, 0.0));
+        transform.set_scale(Vec2::new(2.0, 2.0));
+        transform.rotate(std::f32::consts::FRAC_PI_2);
+        let point = transform
+            .get_matrix()
+            .transform_point(Vec3::new(0.25, 0.0, 0.0));
+        assert!((point - Vec3::new(0.5, 0.5, 0.0)).length() < 1e-6);
+    }
+}
@@ -1,39 +1,196 @@
-mod databuffer;
+pub mod databuffer;
+pub mod layout;
 pub mod texture;
+pub mod triangulation;
 pub mod vertices;
 
+use crate::camera::Camera2D;
 use crate::color::RGBA;
-use crate::polygons::databuffer::DataBuffer;
+use crate::error::Error;
+use crate::math::matrix::Mat4;
+use crate::math::transform::Transform;
+use crate::polygons::databuffer::{BufferUsage, DataBuffer};
+use crate::polygons::texture::units::bind_samplers;
 use crate::polygons::texture::Texture;
 use crate::polygons::vertices::{VertexLocation, Vertices};
+use crate::shaders::preprocessor::ShaderDefines;
+use crate::shaders::registry::with_shader_registry;
 use crate::shaders::shader_program::ShaderProgram;
-use crate::shaders::utils::create_shader_program;
+use crate::shaders::uniform::Uniform;
 
 pub struct Polygon {
     vertices: Vertices,
+    color: Option<RGBA>,
     shader_program: ShaderProgram,
+    is_custom_shader: bool,
     data_buffer: DataBuffer,
-    texture: Option<Texture>,
+    textures: Vec<(String, Texture)>,
+    transform: Transform,
 }
 
 pub struct PolygonBuilder {
     vertices: Vertices,
     color: Option<RGBA>,
-    texture: Option<Texture>,
+    textures: Vec<(String, Texture)>,
+    shader_program: Option<ShaderProgram>,
+    usage: BufferUsage,
+    transform: Transform,
 }
 
+/// Sampler of the built-in texture shader
+const DEFAULT_SAMPLER: &str = "ourTexture";
+
+/// Uniforms the built-in shaders must declare
+const MATRIX_UNIFORMS: [&str; 2] = ["model", "viewProjection"];
+
 impl Polygon {
-    /// # Panics
+    /// Draws in normalized device coordinates
     pub fn draw(&self) {
-        if self.texture.is_some() {
-            self.texture.as_ref().unwrap().draw();
-        }
+        self.draw_with_view_projection(&Mat4::IDENTITY);
+    }
+
+    /// Draws in the world coordinates of `camera`
+    pub fn draw_with_camera(&self, camera: &Camera2D) {
+        self.draw_with_view_projection(&camera.get_view_projection());
+    }
+
+    fn draw_with_view_projection(&self, view_projection: &Mat4) {
         self.shader_program.activate();
+        bind_samplers(
+            &self.shader_program,
+            self.textures
+                .iter()
+                .map(|(name, texture)| (name.as_str(), texture)),
+        );
+        self.set_draw_uniform("model", &self.transform);
+        self.set_draw_uniform("viewProjection", view_projection);
         self.data_buffer.draw();
     }
-}
 
-//TODO IMPLEMENT ERROR HANDLING, CHANGE STRING TO ERR STRUCT
+    /// Built-in shaders are checked for the matrices in
+    /// `PolygonBuilder::build`, custom shaders may leave them out
+    fn set_draw_uniform<T: Uniform + ?Sized>(&self, name: &str, value: &T) {
+        if let Err(error) = self.shader_program.set_uniform(name, value) {
+            assert!(self.is_custom_shader, "built-in polygon shader: {error}");
+        }
+    }
+
+    /// Moves, rotates and scales the polygon without touching its vertices
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    #[must_use]
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    #[must_use]
+    pub fn get_shader_program(&self) -> &ShaderProgram {
+        &self.shader_program
+    }
+
+    /// E.g. for `HotReload`. A built-in shader must keep the `model` and
+    /// `viewProjection` uniforms unless `set_custom_shader` is called.
+    pub fn get_shader_program_mut(&mut self) -> &mut ShaderProgram {
+        &mut self.shader_program
+    }
+
+    /// Custom shaders may leave out the `model` and `viewProjection`
+    /// uniforms, shaders set with `PolygonBuilder::set_shader_program` are
+    /// custom from the start
+    pub fn set_custom_shader(&mut self, is_custom_shader: bool) {
+        self.is_custom_shader = is_custom_shader;
+    }
+
+    #[must_use]
+    pub fn is_custom_shader(&self) -> bool {
+        self.is_custom_shader
+    }
+
+    /// Texture bound to the sampler uniform `name`
+    #[must_use]
+    pub fn get_texture(&self, name: &str) -> Option<&Texture> {
+        self.textures
+            .iter()
+            .find(|(sampler, _)| sampler == name)
+            .map(|(_, texture)| texture)
+    }
+
+    #[must_use]
+    pub fn get_textures(&self) -> &[(String, Texture)] {
+        &self.textures
+    }
+
+    /// Replaces the geometry, the polygon keeps its color and texture.
+    /// The outline is triangulated again, the vertex buffer is rewritten in
+    /// place when its size does not change.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the new vertices have a different layout or do
+    /// not form a valid polygon
+    pub fn set_vertices(&mut self, mut vertices: Vertices) -> Result<(), Error> {
+        prepare_vertices(
+            &mut vertices,
+            self.color.as_ref(),
+            !self.textures.is_empty(),
+        );
+        if vertices.get_layout() != self.vertices.get_layout() {
+            return Err(Error::InvalidVertexLayout(String::from(
+                "new vertices have a different layout than the polygon",
+            )));
+        }
+
+        // the outline may change shape even if the number of vertices stays
+        let indices = triangulate(&vertices)?;
+        self.data_buffer.update(&vertices)?;
+        self.data_buffer.update_indices(&indices);
+        self.vertices = vertices;
+        Ok(())
+    }
+
+    /// Moves `positions.len() / 3` vertices starting at `first_vertex`,
+    /// only this part of the vertex buffer is uploaded again.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the positions do not fit in the existing vertices
+    pub fn update_positions(
+        &mut self,
+        first_vertex: usize,
+        positions: &[f32],
+    ) -> Result<(), Error> {
+        if !self
+            .vertices
+            .set_position_range(first_vertex, positions, VertexLocation::Position)
+        {
+            return Err(Error::InvalidVertexLayout(format!(
+                "{} positions do not fit after vertex {first_vertex}",
+                positions.len()
+            )));
+        }
+
+        let stride = self.data_buffer.get_stride();
+        let bytes: Vec<u8> = self
+            .vertices
+            .create_vertices_range(first_vertex, positions.len() / 3)
+            .ok_or_else(|| Error::InvalidVertexLayout(String::from("invalid vertices structure")))?
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        self.data_buffer.update_range(first_vertex * stride, &bytes)
+    }
+
+    #[must_use]
+    pub fn get_vertices(&self) -> &Vertices {
+        &self.vertices
+    }
+}
 
 impl PolygonBuilder {
     #[must_use]
@@ -41,19 +198,40 @@ impl PolygonBuilder {
         PolygonBuilder {
             vertices: Vertices::empty(),
             color: None,
-            texture: None,
+            textures: vec![],
+            shader_program: None,
+            usage: BufferUsage::Static,
+            transform: Transform::IDENTITY,
         }
     }
 
     /// # Errors
-    pub fn build(&mut self) -> Result<Polygon, String> {
-        self.prepare_vertices();
-        let shader_program = self.create_shader();
+    ///
+    /// Will return `Err` if the vertices have an invalid layout, the shader
+    /// program could not be loaded, compiled or linked, it expects vertex
+    /// attributes the vertices do not provide, or it has no sampler uniform
+    /// for one of the textures
+    pub fn build(&mut self) -> Result<Polygon, Error> {
+        prepare_vertices(
+            &mut self.vertices,
+            self.color.as_ref(),
+            !self.textures.is_empty(),
+        );
+        let is_custom_shader = self.shader_program.is_some();
+        let shader_program = match self.shader_program.take() {
+            Some(shader_program) => shader_program,
+            None => self.create_shader()?,
+        };
+        let mut data_buffer = DataBuffer::new();
+        data_buffer.set_usage(self.usage);
         let mut result = Polygon {
             vertices: self.vertices.clone(),
+            color: self.color.clone(),
             shader_program,
-            data_buffer: DataBuffer::new(),
-            texture: self.texture.take(),
+            is_custom_shader,
+            data_buffer,
+            textures: std::mem::take(&mut self.textures),
+            transform: self.transform,
         };
 
         PolygonBuilder::init_polygon(&mut result)?;
@@ -68,54 +246,98 @@ impl PolygonBuilder {
         self.vertices = vertices;
     }
 
+    /// Texture of the built-in texture shader
     pub fn set_texture(&mut self, texture: Texture) {
-        self.texture = Some(texture);
+        self.add_texture(DEFAULT_SAMPLER, texture);
     }
 
-    fn prepare_vertices(&mut self) {
-        if self.color.is_some() {
-            self.vertices
-                .set_one_color_for_all_vert(self.color.as_ref().unwrap());
-        } else {
-            self.vertices
-                .set_one_color_for_all_vert(&RGBA::from_hex(0xFF_FF_FF_FF));
+    /// Binds `texture` to the `sampler2D` uniform `sampler` on every draw,
+    /// replacing the texture previously added for it. Samplers other than
+    /// `ourTexture` need a shader set with `set_shader_program`.
+    pub fn add_texture(&mut self, sampler: &str, texture: Texture) {
+        match self.textures.iter_mut().find(|(name, _)| name == sampler) {
+            Some((_, current)) => *current = texture,
+            None => self.textures.push((String::from(sampler), texture)),
         }
+    }
 
-        if self.texture.is_some() && !self.vertices.is_texture() {
-            if self.vertices.is_triangle() {
-                self.vertices
-                    .set_position(&[1.0, 1.0, 1.0, 0.0, 0.0, 0.0], VertexLocation::Texture);
-            } else if self.vertices.is_reactangle() {
-                self.vertices.set_position(
-                    &[1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
-                    VertexLocation::Texture,
-                );
-            }
-        }
+    /// Replaces the built-in shader, it receives the same vertex attributes
+    /// and the `model` and `viewProjection` uniforms
+    pub fn set_shader_program(&mut self, shader_program: ShaderProgram) {
+        self.shader_program = Some(shader_program);
     }
 
-    fn create_shader(&self) -> ShaderProgram {
-        if self.texture.is_some() {
-            create_shader_program("basic_texture.vert", "basic_texture.frag")
-        } else {
-            create_shader_program("basic_colored.vert", "basic_colored.frag")
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    /// Use `BufferUsage::Dynamic` or `BufferUsage::Stream` for polygons
+    /// updated with `Polygon::set_vertices` or `Polygon::update_positions`
+    pub fn set_usage(&mut self, usage: BufferUsage) {
+        self.usage = usage;
+    }
+
+    /// Variant of `basic.vert` and `basic.frag`, the vertices always carry
+    /// a color
+    fn create_shader(&self) -> Result<ShaderProgram, Error> {
+        let mut defines = ShaderDefines::new();
+        defines.set_flag("VERTEX_COLOR");
+        if !self.textures.is_empty() {
+            defines.set_flag("TEXTURED");
         }
+        with_shader_registry(|registry| {
+            registry.create_variant("basic.vert", "basic.frag", &defines)
+        })
     }
 
-    fn init_polygon(polygon: &mut Polygon) -> Result<(), String> {
-        if polygon.texture.is_some() {
-            polygon.texture.as_mut().unwrap().generate_mipmap();
+    fn init_polygon(polygon: &mut Polygon) -> Result<(), Error> {
+        for (_, texture) in &mut polygon.textures {
+            texture.generate_mipmap();
         }
 
-        polygon.data_buffer.init(&polygon.vertices)?;
-        if !polygon.shader_program.compile() {
-            return Err(String::from("Shader program compilation error"));
+        let indices = triangulate(&polygon.vertices)?;
+        polygon.data_buffer.init(&polygon.vertices, &indices)?;
+        polygon.shader_program.compile()?;
+        if !polygon.is_custom_shader {
+            polygon.shader_program.require_uniforms(&MATRIX_UNIFORMS)?;
         }
+        let samplers: Vec<&str> = polygon
+            .textures
+            .iter()
+            .map(|(sampler, _)| sampler.as_str())
+            .collect();
+        polygon.shader_program.require_uniforms(&samplers)?;
+        polygon
+            .shader_program
+            .validate_layout(&polygon.vertices.get_layout())
+    }
+}
 
-        Ok(())
+fn prepare_vertices(vertices: &mut Vertices, color: Option<&RGBA>, is_texture: bool) {
+    if let Some(color) = color {
+        vertices.set_one_color_for_all_vert(color);
+    } else {
+        vertices.set_one_color_for_all_vert(&RGBA::from_hex(0xFF_FF_FF_FF));
+    }
+
+    if is_texture && !vertices.is_texture() {
+        if vertices.is_triangle() {
+            vertices.set_position(&[1.0, 1.0, 1.0, 0.0, 0.0, 0.0], VertexLocation::Texture);
+        } else {
+            let coordinates = vertices.bounding_box_texture_coordinates();
+            vertices.set_position(&coordinates, VertexLocation::Texture);
+        }
     }
 }
 
+fn triangulate(vertices: &Vertices) -> Result<Vec<u32>, Error> {
+    vertices.triangulate().ok_or_else(|| {
+        Error::InvalidVertexLayout(String::from(
+            "positions do not form a simple polygon with at least 3 vertices",
+        ))
+    })
+}
+
 impl Default for PolygonBuilder {
     fn default() -> Self {
         Self::new()
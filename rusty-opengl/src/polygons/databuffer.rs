@@ -1,11 +1,27 @@
+use crate::error::Error;
+use crate::polygons::layout::VertexLayout;
 use crate::polygons::vertices::Vertices;
 use gl;
-use std::ptr;
+
+/// Hint how often the vertex data is going to be replaced
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferUsage {
+    /// Uploaded once, drawn many times
+    Static = 0x88E4,
+    /// Replaced from time to time
+    Dynamic = 0x88E8,
+    /// Replaced every frame
+    Stream = 0x88E0,
+}
 
 pub struct DataBuffer {
     vao: u32,
     vbo: u32,
     ebo: u32,
+    indices_count: i32,
+    usage: BufferUsage,
+    stride: usize,
+    size: usize,
 }
 
 impl DataBuffer {
@@ -15,37 +31,141 @@ impl DataBuffer {
             vao: 0,
             vbo: 0,
             ebo: 0,
+            indices_count: 0,
+            usage: BufferUsage::Static,
+            stride: 0,
+            size: 0,
         }
     }
 
-    pub fn init(&mut self, data: &Vertices) -> Result<(), String> {
+    /// Usage hint applied on the next full upload
+    pub fn set_usage(&mut self, usage: BufferUsage) {
+        self.usage = usage;
+    }
+
+    #[must_use]
+    pub fn get_usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if colors or texture coordinates do not match the
+    /// number of positions
+    pub fn init(&mut self, data: &Vertices, indices: &[u32]) -> Result<(), Error> {
+        let bytes = DataBuffer::vertices_as_bytes(data)?;
+        self.init_with_layout(&data.get_layout(), &bytes, indices)
+    }
+
+    /// Uploads interleaved vertex data described by `layout`, e.g. produced by
+    /// `VertexLayout::interleave`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `data` does not hold a whole number of vertices
+    pub fn init_with_layout(
+        &mut self,
+        layout: &VertexLayout,
+        data: &[u8],
+        indices: &[u32],
+    ) -> Result<(), Error> {
+        self.stride = layout.get_stride();
+        self.check_whole_vertices(data.len())?;
+
         self.generate_buffers();
         self.bind();
-        DataBuffer::init_buffer(data)?;
-        if data.is_reactangle() {
-            self.generate_ebo_buffer();
-            self.set_attribute_element_array_buffer();
+        self.init_buffer(data);
+        self.generate_ebo_buffer();
+        self.set_attribute_element_array_buffer(indices);
+        layout.apply();
+
+        DataBuffer::unbind();
+        Ok(())
+    }
+
+    /// Replaces the whole vertex buffer, the layout has to stay the same
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if colors or texture coordinates do not match the
+    /// number of positions
+    pub fn update(&mut self, data: &Vertices) -> Result<(), Error> {
+        let bytes = DataBuffer::vertices_as_bytes(data)?;
+        self.update_raw(&bytes)
+    }
+
+    /// Replaces the whole vertex buffer with interleaved data of the layout
+    /// used in `init_with_layout`.
+    ///
+    /// Data of the same size is written in place. For `Dynamic` and `Stream`
+    /// buffers the old storage is orphaned first, so the driver does not have
+    /// to wait until draws still using it are finished.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `data` does not hold a whole number of vertices
+    pub fn update_raw(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.check_whole_vertices(data.len())?;
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            if data.len() == self.size {
+                if self.usage != BufferUsage::Static {
+                    self.orphan_buffer();
+                }
+                DataBuffer::write_sub_data(0, data);
+            } else {
+                self.init_buffer(data);
+            }
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
         }
+        Ok(())
+    }
 
-        DataBuffer::set_position_attribute_ptr(data.get_stride());
-        DataBuffer::set_color_attribute_ptr(data.get_stride());
-        if data.is_texture() {
-            DataBuffer::set_attribute_ptr_for_texture(data.get_stride());
+    /// Overwrites part of the vertex buffer starting at `offset` bytes
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the range does not fit in the buffer
+    pub fn update_range(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+        if offset + data.len() > self.size {
+            return Err(Error::InvalidVertexLayout(format!(
+                "range {offset}..{} exceeds the {} byte buffer",
+                offset + data.len(),
+                self.size
+            )));
         }
 
-        DataBuffer::unbind();
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            DataBuffer::write_sub_data(offset, data);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
         Ok(())
     }
 
+    pub fn update_indices(&mut self, indices: &[u32]) {
+        unsafe {
+            gl::BindVertexArray(self.vao);
+        }
+        self.set_attribute_element_array_buffer(indices);
+        DataBuffer::unbind();
+    }
+
+    #[must_use]
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
     pub fn draw(&self) {
         unsafe {
             gl::BindVertexArray(self.vao);
-            if self.ebo > 0 {
-                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER as u32, self.ebo);
-                gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, std::ptr::null());
-            } else {
-                gl::DrawArrays(gl::TRIANGLES, 0, 3);
-            }
+            gl::DrawElements(
+                gl::TRIANGLES,
+                self.indices_count,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            gl::BindVertexArray(0);
         }
     }
 
@@ -66,73 +186,72 @@ impl DataBuffer {
         }
     }
 
-    fn init_buffer(vertices: &Vertices) -> Result<(), String> {
-        let size = vertices.sum_capacity() * std::mem::size_of::<f32>();
-        let raw_data = vertices.create_single_vertices_array();
-        if raw_data.is_none() {
-            return Err(String::from("Invalid vertices structure"));
-        }
-
+    fn init_buffer(&mut self, data: &[u8]) {
+        self.size = data.len();
         unsafe {
             gl::BufferData(
                 gl::ARRAY_BUFFER,
-                size.try_into().unwrap(),
-                raw_data.unwrap().as_ptr().cast::<std::ffi::c_void>(),
-                gl::STATIC_DRAW,
+                data.len().try_into().unwrap(),
+                data.as_ptr().cast::<std::ffi::c_void>(),
+                self.usage as u32,
             );
         }
-
-        Ok(())
     }
 
-    fn set_attribute_element_array_buffer(&mut self) {
-        let indices: [u32; 6] = [0, 1, 3, 1, 2, 3];
+    fn orphan_buffer(&self) {
         unsafe {
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER as u32, self.ebo);
             gl::BufferData(
-                gl::ELEMENT_ARRAY_BUFFER as u32,
-                (indices.len() * std::mem::size_of::<f32>())
-                    .try_into()
-                    .unwrap(),
-                indices.as_ptr().cast::<std::ffi::c_void>(),
-                gl::STATIC_DRAW,
+                gl::ARRAY_BUFFER,
+                self.size.try_into().unwrap(),
+                std::ptr::null(),
+                self.usage as u32,
             );
         }
     }
 
-    fn set_position_attribute_ptr(stride: i32) {
+    fn write_sub_data(offset: usize, data: &[u8]) {
         unsafe {
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-            gl::EnableVertexAttribArray(0);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                offset.try_into().unwrap(),
+                data.len().try_into().unwrap(),
+                data.as_ptr().cast::<std::ffi::c_void>(),
+            );
         }
     }
 
-    fn set_color_attribute_ptr(stride: i32) {
-        unsafe {
-            let ptr = 3 * std::mem::size_of::<f32>();
-            gl::VertexAttribPointer(
-                1,
-                4,
-                gl::FLOAT,
-                gl::FALSE,
-                stride,
-                ptr as *const std::ffi::c_void,
-            );
-            gl::EnableVertexAttribArray(1);
+    fn check_whole_vertices(&self, size: usize) -> Result<(), Error> {
+        if self.stride == 0 || !size.is_multiple_of(self.stride) {
+            return Err(Error::InvalidVertexLayout(format!(
+                "{size} bytes is not a multiple of the {} byte stride",
+                self.stride
+            )));
         }
+        Ok(())
+    }
+
+    fn vertices_as_bytes(data: &Vertices) -> Result<Vec<u8>, Error> {
+        let raw_data = data.create_single_vertices_array().ok_or_else(|| {
+            Error::InvalidVertexLayout(String::from(
+                "colors and texture coordinates do not match the number of positions",
+            ))
+        })?;
+        Ok(raw_data
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect())
     }
 
-    fn set_attribute_ptr_for_texture(stride: i32) {
+    fn set_attribute_element_array_buffer(&mut self, indices: &[u32]) {
+        self.indices_count = indices.len().try_into().unwrap();
         unsafe {
-            gl::VertexAttribPointer(
-                2,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                stride,
-                (7 * std::mem::size_of::<f32>()) as *const std::ffi::c_void,
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices).try_into().unwrap(),
+                indices.as_ptr().cast::<std::ffi::c_void>(),
+                self.usage as u32,
             );
-            gl::EnableVertexAttribArray(2);
         }
     }
 
@@ -145,13 +264,19 @@ impl DataBuffer {
 
     fn unbind() {
         unsafe {
+            gl::BindVertexArray(0);
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
         }
     }
 }
 
+impl Default for DataBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for DataBuffer {
     fn drop(&mut self) {
         unsafe {
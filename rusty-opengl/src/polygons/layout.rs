@@ -0,0 +1,326 @@
+use crate::error::Error;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    F32,
+    I8,
+    I8Normalized,
+    U8,
+    U8Normalized,
+    I16,
+    I16Normalized,
+    U16,
+    U16Normalized,
+    I32,
+    U32,
+}
+
+impl AttributeType {
+    #[must_use]
+    pub fn size(self) -> usize {
+        match self {
+            AttributeType::I8
+            | AttributeType::I8Normalized
+            | AttributeType::U8
+            | AttributeType::U8Normalized => 1,
+            AttributeType::I16
+            | AttributeType::I16Normalized
+            | AttributeType::U16
+            | AttributeType::U16Normalized => 2,
+            AttributeType::F32 | AttributeType::I32 | AttributeType::U32 => 4,
+        }
+    }
+
+    #[must_use]
+    pub fn gl_type(self) -> u32 {
+        match self {
+            AttributeType::F32 => gl::FLOAT,
+            AttributeType::I8 | AttributeType::I8Normalized => gl::BYTE,
+            AttributeType::U8 | AttributeType::U8Normalized => gl::UNSIGNED_BYTE,
+            AttributeType::I16 | AttributeType::I16Normalized => gl::SHORT,
+            AttributeType::U16 | AttributeType::U16Normalized => gl::UNSIGNED_SHORT,
+            AttributeType::I32 => gl::INT,
+            AttributeType::U32 => gl::UNSIGNED_INT,
+        }
+    }
+
+    #[must_use]
+    pub fn is_normalized(self) -> bool {
+        matches!(
+            self,
+            AttributeType::I8Normalized
+                | AttributeType::U8Normalized
+                | AttributeType::I16Normalized
+                | AttributeType::U16Normalized
+        )
+    }
+
+    /// Integer types that are not normalized, shaders read them as `int`,
+    /// `uint` or their vectors
+    #[must_use]
+    pub fn is_integer(self) -> bool {
+        !matches!(self, AttributeType::F32) && !self.is_normalized()
+    }
+
+    /// Appends `value` converted to this type, normalized types expect
+    /// values in [0, 1] (unsigned) or [-1, 1] (signed)
+    fn write(self, value: f32, buffer: &mut Vec<u8>) {
+        match self {
+            AttributeType::F32 => buffer.extend_from_slice(&value.to_ne_bytes()),
+            AttributeType::I8 => buffer.extend_from_slice(&(value as i8).to_ne_bytes()),
+            AttributeType::I8Normalized => buffer
+                .extend_from_slice(&((value.clamp(-1.0, 1.0) * 127.0).round() as i8).to_ne_bytes()),
+            AttributeType::U8 => buffer.push(value as u8),
+            AttributeType::U8Normalized => {
+                buffer.push((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+            }
+            AttributeType::I16 => buffer.extend_from_slice(&(value as i16).to_ne_bytes()),
+            AttributeType::I16Normalized => buffer.extend_from_slice(
+                &((value.clamp(-1.0, 1.0) * 32767.0).round() as i16).to_ne_bytes(),
+            ),
+            AttributeType::U16 => buffer.extend_from_slice(&(value as u16).to_ne_bytes()),
+            AttributeType::U16Normalized => buffer.extend_from_slice(
+                &((value.clamp(0.0, 1.0) * 65535.0).round() as u16).to_ne_bytes(),
+            ),
+            AttributeType::I32 => buffer.extend_from_slice(&(value as i32).to_ne_bytes()),
+            AttributeType::U32 => buffer.extend_from_slice(&(value as u32).to_ne_bytes()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub name: String,
+    pub location: u32,
+    pub components: usize,
+    pub attribute_type: AttributeType,
+}
+
+impl VertexAttribute {
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.components * self.attribute_type.size()
+    }
+}
+
+/// Description of interleaved vertex data, attributes are stored in the
+/// order they were added
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    #[must_use]
+    pub fn new() -> Self {
+        VertexLayout { attributes: vec![] }
+    }
+
+    #[must_use]
+    pub fn with_attribute(
+        mut self,
+        name: &str,
+        location: u32,
+        components: usize,
+        attribute_type: AttributeType,
+    ) -> Self {
+        self.push_attribute(name, location, components, attribute_type);
+        self
+    }
+
+    pub fn push_attribute(
+        &mut self,
+        name: &str,
+        location: u32,
+        components: usize,
+        attribute_type: AttributeType,
+    ) {
+        self.attributes.push(VertexAttribute {
+            name: String::from(name),
+            location,
+            components,
+            attribute_type,
+        });
+    }
+
+    #[must_use]
+    pub fn get_attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    #[must_use]
+    pub fn get_attribute(&self, name: &str) -> Option<&VertexAttribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Size of a single vertex in bytes
+    #[must_use]
+    pub fn get_stride(&self) -> usize {
+        self.attributes.iter().map(VertexAttribute::size).sum()
+    }
+
+    /// Byte offset of the attribute at `index` inside a vertex
+    #[must_use]
+    pub fn get_offset(&self, index: usize) -> usize {
+        self.attributes
+            .iter()
+            .take(index)
+            .map(VertexAttribute::size)
+            .sum()
+    }
+
+    /// Interleaves one stream of values per attribute into a vertex buffer,
+    /// converting every value to the attribute type.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the number of streams does not match the number of
+    /// attributes or the streams describe a different number of vertices
+    pub fn interleave(&self, streams: &[&[f32]]) -> Result<Vec<u8>, Error> {
+        if streams.len() != self.attributes.len() {
+            return Err(Error::InvalidVertexLayout(format!(
+                "layout has {} attributes but {} streams were provided",
+                self.attributes.len(),
+                streams.len()
+            )));
+        }
+
+        let vertices = self.count_vertices(streams)?;
+        let mut result = Vec::with_capacity(vertices * self.get_stride());
+        for vertex in 0..vertices {
+            for (attribute, stream) in self.attributes.iter().zip(streams) {
+                let start = vertex * attribute.components;
+                for value in &stream[start..start + attribute.components] {
+                    attribute.attribute_type.write(*value, &mut result);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Sets up attribute pointers for the currently bound VAO and array buffer.
+    /// Integer types use `glVertexAttribIPointer`, so shaders receive them
+    /// unconverted.
+    pub fn apply(&self) {
+        let stride = i32::try_from(self.get_stride()).unwrap();
+        for (index, attribute) in self.attributes.iter().enumerate() {
+            let attribute_type = attribute.attribute_type;
+            let components = i32::try_from(attribute.components).unwrap();
+            let offset = self.get_offset(index) as *const std::ffi::c_void;
+            unsafe {
+                if attribute_type.is_integer() {
+                    gl::VertexAttribIPointer(
+                        attribute.location,
+                        components,
+                        attribute_type.gl_type(),
+                        stride,
+                        offset,
+                    );
+                } else {
+                    let normalized = if attribute_type.is_normalized() {
+                        gl::TRUE
+                    } else {
+                        gl::FALSE
+                    };
+                    gl::VertexAttribPointer(
+                        attribute.location,
+                        components,
+                        attribute_type.gl_type(),
+                        normalized,
+                        stride,
+                        offset,
+                    );
+                }
+                gl::EnableVertexAttribArray(attribute.location);
+            }
+        }
+    }
+
+    fn count_vertices(&self, streams: &[&[f32]]) -> Result<usize, Error> {
+        let mut result = None;
+        for (attribute, stream) in self.attributes.iter().zip(streams) {
+            if attribute.components == 0 || stream.len() % attribute.components != 0 {
+                return Err(Error::InvalidVertexLayout(format!(
+                    "attribute {} expects {} components per vertex",
+                    attribute.name, attribute.components
+                )));
+            }
+
+            let count = stream.len() / attribute.components;
+            if *result.get_or_insert(count) != count {
+                return Err(Error::InvalidVertexLayout(format!(
+                    "attribute {} describes a different number of vertices",
+                    attribute.name
+                )));
+            }
+        }
+        Ok(result.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position_color_layout() -> VertexLayout {
+        VertexLayout::new()
+            .with_attribute("aPos", 0, 2, AttributeType::F32)
+            .with_attribute("aColor", 1, 4, AttributeType::U8Normalized)
+    }
+
+    #[test]
+    fn test_layout_stride_and_offsets() {
+        let layout = position_color_layout().with_attribute("aNormal", 2, 3, AttributeType::I16);
+        assert_eq!(8 + 4 + 6, layout.get_stride());
+        assert_eq!(0, layout.get_offset(0));
+        assert_eq!(8, layout.get_offset(1));
+        assert_eq!(12, layout.get_offset(2));
+        assert_eq!(2, layout.get_attribute("aNormal").unwrap().location);
+    }
+
+    #[test]
+    fn test_attribute_pointer_dispatch() {
+        let integers = [
+            AttributeType::I8,
+            AttributeType::U8,
+            AttributeType::I16,
+            AttributeType::U16,
+            AttributeType::I32,
+            AttributeType::U32,
+        ];
+        let floats = [
+            AttributeType::F32,
+            AttributeType::I8Normalized,
+            AttributeType::U8Normalized,
+            AttributeType::I16Normalized,
+            AttributeType::U16Normalized,
+        ];
+        assert!(integers.iter().all(|value| value.is_integer()));
+        assert!(floats.iter().all(|value| !value.is_integer()));
+    }
+
+    #[test]
+    fn test_layout_interleave() {
+        let layout = position_color_layout();
+        let positions = [0.5_f32, -0.5, 1.0, 1.0];
+        let colors = [1.0_f32, 0.0, 0.5, 1.0, 0.0, 1.0, 0.0, 0.0];
+        let result = layout.interleave(&[&positions, &colors]).unwrap();
+
+        assert_eq!(2 * layout.get_stride(), result.len());
+        assert_eq!(&0.5_f32.to_ne_bytes(), &result[0..4]);
+        assert_eq!(&(-0.5_f32).to_ne_bytes(), &result[4..8]);
+        assert_eq!(&[255, 0, 128, 255], &result[8..12]);
+        assert_eq!(&[0, 255, 0, 0], &result[20..24]);
+    }
+
+    #[test]
+    fn test_layout_interleave_mismatched_streams() {
+        let layout = position_color_layout();
+        let positions = [0.5_f32, -0.5, 1.0, 1.0];
+        let colors = [1.0_f32, 0.0, 0.5, 1.0];
+        assert!(layout.interleave(&[&positions, &colors]).is_err());
+        assert!(layout.interleave(&[&positions]).is_err());
+    }
+}
@@ -1,4 +1,13 @@
+pub mod atlas;
+pub mod generators;
+pub mod units;
+
+use crate::error::Error;
+use crate::polygons::texture::units::{forget_texture, with_texture_units};
+use image::{DynamicImage, ImageError};
+use std::io::ErrorKind;
 use std::path::Path;
+use std::rc::Rc;
 
 #[derive(Copy, Clone)]
 pub enum Filtering {
@@ -14,50 +23,165 @@ pub enum Wrapping {
     ClampToBorder = 0x812D,
 }
 
+/// Layout of the texels, chosen from the color type of the loaded image
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Grayscale, sampled as `(l, l, l, 1)`
+    R8,
+    /// Grayscale with alpha, sampled as `(l, l, l, a)`
+    Rg8,
+    Rgb8,
+    Rgba8,
+    /// 16 bits per channel, used for every 16-bit and floating point image
+    Rgba16,
+    Srgb8,
+    Srgb8Alpha8,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Puts the first row of the file at the bottom, where OpenGL expects it
+    pub flip_vertically: bool,
+    /// Multiplies the color channels by alpha, for `gl::ONE, gl::ONE_MINUS_SRC_ALPHA` blending
+    pub premultiply_alpha: bool,
+    /// 8-bit color images are decoded from sRGB when sampled
+    pub srgb: bool,
+}
+
+/// Clones share the same OpenGL texture, it is deleted with the last clone
 #[derive(Clone)]
 pub struct Texture {
-    id: u32,
+    handle: Rc<TextureHandle>,
     width: u32,
     height: u32,
+    format: PixelFormat,
     data: Vec<u8>,
 }
 
+struct TextureHandle(u32);
+
 impl Texture {
-    /// # Panics
+    /// # Errors
     ///
-    /// Will panic if provided path to the image is invalid
+    /// Will return `Err` if the image file does not exist or could not be decoded
+    pub fn new(image_path: &Path) -> Result<Self, Error> {
+        Texture::load(image_path, LoadOptions::default())
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the image file does not exist or could not be decoded
+    pub fn load(image_path: &Path, options: LoadOptions) -> Result<Self, Error> {
+        Ok(Texture::from_image(open_image(image_path)?, options))
+    }
+
+    /// Decodes an encoded image (PNG, JPEG, ...), e.g. from `include_bytes!`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the format is unknown or the data could not be decoded
+    pub fn from_memory(bytes: &[u8], options: LoadOptions) -> Result<Self, Error> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|error| Error::ImageDecode(error.to_string()))?;
+        Ok(Texture::from_image(img, options))
+    }
+
     #[must_use]
-    pub fn new(image_path: &Path) -> Self {
-        if let Ok(img) = image::open(&image_path) {
-            let mut id = 0;
-            unsafe {
-                gl::GenTextures(1, &mut id);
-            }
-            Texture {
-                id,
-                width: img.width(),
-                height: img.height(),
-                data: img.into_bytes(),
-            }
-        } else {
-            panic!(
-                "Failed to load texture at path {}",
-                image_path.to_str().unwrap()
+    pub fn from_image(img: DynamicImage, options: LoadOptions) -> Self {
+        let (width, height) = (img.width(), img.height());
+        let (format, data) = decode_image(img, options);
+        Texture {
+            handle: Rc::new(TextureHandle::new()),
+            width,
+            height,
+            format,
+            data,
+        }
+    }
+
+    /// Rows are tightly packed, bottom row first, 16-bit channels are in
+    /// native byte order
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the length of `data` does not match the size and format
+    pub fn from_raw(
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        data: Vec<u8>,
+    ) -> Result<Self, Error> {
+        let expected = width as usize * height as usize * format.get_bytes_per_pixel();
+        if data.len() != expected || expected == 0 {
+            return Err(Error::InvalidTextureData(format!(
+                "{width}x{height} {format:?} needs {expected} bytes, got {}",
+                data.len()
+            )));
+        }
+
+        Ok(Texture {
+            handle: Rc::new(TextureHandle::new()),
+            width,
+            height,
+            format,
+            data,
+        })
+    }
+
+    /// RGBA texture without contents, used as a render target attachment
+    pub(crate) fn empty(width: u32, height: u32) -> Self {
+        let result = Texture {
+            handle: Rc::new(TextureHandle::new()),
+            width,
+            height,
+            format: PixelFormat::Rgba8,
+            data: vec![],
+        };
+        result.bind_for_update();
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as i32,
+                i32::try_from(width).unwrap_or(i32::MAX),
+                i32::try_from(height).unwrap_or(i32::MAX),
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
             );
         }
+        result.set_filtering(Filtering::Linear);
+        result.set_wrapping(Wrapping::ClampToEdge);
+        result
+    }
+
+    #[must_use]
+    pub fn get_id(&self) -> u32 {
+        self.handle.0
+    }
+
+    #[must_use]
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[must_use]
+    pub fn get_format(&self) -> PixelFormat {
+        self.format
     }
 
     pub fn set_filtering(&self, filtering: Filtering) {
+        self.bind_for_update();
         unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.id);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filtering as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filtering as i32);
         }
     }
 
     pub fn set_wrapping(&self, wrapping: Wrapping) {
+        self.bind_for_update();
         unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.id);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrapping as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrapping as i32);
         }
@@ -65,8 +189,8 @@ impl Texture {
 
     /// # Panics
     pub fn set_default(&mut self) {
+        self.bind_for_update();
         unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.id);
             gl::TexParameteri(
                 gl::TEXTURE_2D,
                 gl::TEXTURE_WRAP_S,
@@ -90,37 +214,275 @@ impl Texture {
         }
     }
 
-    /// # Panics
-    ///
-    /// Will panic if provided image is invalid
+    /// Uploads the image data and builds the mipmap chain, textures without
+    /// data (e.g. render target attachments) are left untouched.
     pub fn generate_mipmap(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        self.upload();
+        unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+    }
+
+    /// Uploads the image data without mipmaps, for textures that are only
+    /// sampled with a non-mipmapped min filter
+    pub fn upload(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        let format = self.format;
+        self.bind_for_update();
         unsafe {
+            let mut previous_alignment = 0;
+            gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut previous_alignment);
+            // rows of odd widths are not padded to 4 bytes
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGB.try_into().unwrap(),
-                self.width.try_into().unwrap(),
-                self.height.try_into().unwrap(),
+                i32::try_from(format.get_internal_format()).unwrap_or(0),
+                i32::try_from(self.width).unwrap_or(i32::MAX),
+                i32::try_from(self.height).unwrap_or(i32::MAX),
                 0,
-                gl::RGB,
-                gl::UNSIGNED_BYTE,
+                format.get_format(),
+                format.get_data_type(),
                 self.data.as_ptr().cast::<std::ffi::c_void>(),
             );
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, previous_alignment);
+            if let Some(swizzle) = format.get_swizzle() {
+                gl::TexParameteriv(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_SWIZZLE_RGBA,
+                    swizzle.map(|channel| channel as i32).as_ptr(),
+                );
+            }
         }
     }
 
+    /// Binds the texture to a unit chosen by the texture unit allocator and
+    /// makes that unit active
     pub fn draw(&self) {
+        self.bind_for_update();
+    }
+
+    fn bind_for_update(&self) {
+        with_texture_units(|units| units.bind(&[self]));
+    }
+}
+
+impl PixelFormat {
+    #[must_use]
+    pub fn get_internal_format(self) -> u32 {
+        match self {
+            PixelFormat::R8 => gl::R8,
+            PixelFormat::Rg8 => gl::RG8,
+            PixelFormat::Rgb8 => gl::RGB8,
+            PixelFormat::Rgba8 => gl::RGBA8,
+            PixelFormat::Rgba16 => gl::RGBA16,
+            PixelFormat::Srgb8 => gl::SRGB8,
+            PixelFormat::Srgb8Alpha8 => gl::SRGB8_ALPHA8,
+        }
+    }
+
+    /// Layout of the uploaded data
+    #[must_use]
+    pub fn get_format(self) -> u32 {
+        match self {
+            PixelFormat::R8 => gl::RED,
+            PixelFormat::Rg8 => gl::RG,
+            PixelFormat::Rgb8 | PixelFormat::Srgb8 => gl::RGB,
+            PixelFormat::Rgba8 | PixelFormat::Rgba16 | PixelFormat::Srgb8Alpha8 => gl::RGBA,
+        }
+    }
+
+    #[must_use]
+    pub fn get_data_type(self) -> u32 {
+        match self {
+            PixelFormat::Rgba16 => gl::UNSIGNED_SHORT,
+            _ => gl::UNSIGNED_BYTE,
+        }
+    }
+
+    #[must_use]
+    pub fn get_channels(self) -> usize {
+        match self {
+            PixelFormat::R8 => 1,
+            PixelFormat::Rg8 => 2,
+            PixelFormat::Rgb8 | PixelFormat::Srgb8 => 3,
+            PixelFormat::Rgba8 | PixelFormat::Rgba16 | PixelFormat::Srgb8Alpha8 => 4,
+        }
+    }
+
+    #[must_use]
+    pub fn get_bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgba16 => 8,
+            other => other.get_channels(),
+        }
+    }
+
+    #[must_use]
+    pub fn has_alpha(self) -> bool {
+        matches!(
+            self,
+            PixelFormat::Rg8 | PixelFormat::Rgba8 | PixelFormat::Rgba16 | PixelFormat::Srgb8Alpha8
+        )
+    }
+
+    /// Grayscale formats repeat the luminance in the color channels
+    fn get_swizzle(self) -> Option<[u32; 4]> {
+        match self {
+            PixelFormat::R8 => Some([gl::RED, gl::RED, gl::RED, gl::ONE]),
+            PixelFormat::Rg8 => Some([gl::RED, gl::RED, gl::RED, gl::GREEN]),
+            _ => None,
+        }
+    }
+}
+
+impl TextureHandle {
+    fn new() -> Self {
+        let mut id = 0;
         unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.id);
+            gl::GenTextures(1, &mut id);
         }
+        TextureHandle(id)
     }
 }
 
-impl Drop for Texture {
+impl Drop for TextureHandle {
     fn drop(&mut self) {
+        forget_texture(self.0);
         unsafe {
-            gl::DeleteTextures(1, &self.id);
+            gl::DeleteTextures(1, &self.0);
         }
     }
 }
+
+pub(crate) fn open_image(image_path: &Path) -> Result<DynamicImage, Error> {
+    image::open(image_path).map_err(|error| match error {
+        ImageError::IoError(io_error) if io_error.kind() == ErrorKind::NotFound => {
+            Error::TextureNotFound(image_path.to_path_buf())
+        }
+        other => Error::ImageDecode(other.to_string()),
+    })
+}
+
+/// Converts `img` to the closest format OpenGL can sample, 16-bit channels
+/// are kept in native byte order
+fn decode_image(img: DynamicImage, options: LoadOptions) -> (PixelFormat, Vec<u8>) {
+    let img = if options.flip_vertically {
+        img.flipv()
+    } else {
+        img
+    };
+    let (format, mut data) = match img {
+        DynamicImage::ImageLuma8(img) => (PixelFormat::R8, img.into_raw()),
+        DynamicImage::ImageLumaA8(img) => (PixelFormat::Rg8, img.into_raw()),
+        DynamicImage::ImageRgb8(img) if options.srgb => (PixelFormat::Srgb8, img.into_raw()),
+        DynamicImage::ImageRgb8(img) => (PixelFormat::Rgb8, img.into_raw()),
+        DynamicImage::ImageRgba8(img) if options.srgb => (PixelFormat::Srgb8Alpha8, img.into_raw()),
+        DynamicImage::ImageRgba8(img) => (PixelFormat::Rgba8, img.into_raw()),
+        other => {
+            let data = other
+                .into_rgba16()
+                .into_raw()
+                .into_iter()
+                .flat_map(u16::to_ne_bytes)
+                .collect();
+            (PixelFormat::Rgba16, data)
+        }
+    };
+    if options.premultiply_alpha {
+        premultiply_alpha(format, &mut data);
+    }
+    (format, data)
+}
+
+/// Scales the color channels of every pixel by its alpha
+fn premultiply_alpha(format: PixelFormat, data: &mut [u8]) {
+    if !format.has_alpha() {
+        return;
+    }
+
+    let channels = format.get_channels();
+    if format == PixelFormat::Rgba16 {
+        for pixel in data.chunks_exact_mut(8) {
+            let alpha = u32::from(u16::from_ne_bytes([pixel[6], pixel[7]]));
+            for channel in pixel[..6].chunks_exact_mut(2) {
+                let value = u32::from(u16::from_ne_bytes([channel[0], channel[1]]));
+                let result = u16::try_from((value * alpha + 32767) / 65535).unwrap_or(u16::MAX);
+                channel.copy_from_slice(&result.to_ne_bytes());
+            }
+        }
+    } else {
+        for pixel in data.chunks_exact_mut(channels) {
+            let alpha = u32::from(pixel[channels - 1]);
+            for channel in &mut pixel[..channels - 1] {
+                *channel = u8::try_from((u32::from(*channel) * alpha + 127) / 255).unwrap_or(255);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, ImageBuffer, Rgba, RgbaImage};
+
+    #[test]
+    fn test_decode_image_formats() {
+        let gray = DynamicImage::ImageLuma8(GrayImage::new(3, 1));
+        let (format, data) = decode_image(gray, LoadOptions::default());
+        assert_eq!(PixelFormat::R8, format);
+        assert_eq!(3, data.len());
+
+        let rgba = DynamicImage::ImageRgba8(RgbaImage::new(1, 1));
+        let options = LoadOptions {
+            srgb: true,
+            ..LoadOptions::default()
+        };
+        assert_eq!(PixelFormat::Srgb8Alpha8, decode_image(rgba, options).0);
+
+        let deep: ImageBuffer<Rgba<u16>, Vec<u16>> =
+            ImageBuffer::from_pixel(2, 1, Rgba([1, 2, 3, u16::MAX]));
+        let (format, data) = decode_image(DynamicImage::ImageRgba16(deep), options);
+        assert_eq!(PixelFormat::Rgba16, format);
+        assert_eq!(2 * format.get_bytes_per_pixel(), data.len());
+        assert_eq!(3_u16.to_ne_bytes(), [data[4], data[5]]);
+    }
+
+    #[test]
+    fn test_decode_image_flips_and_premultiplies() {
+        let mut img = RgbaImage::new(1, 2);
+        img.put_pixel(0, 0, Rgba([200, 100, 50, 255]));
+        img.put_pixel(0, 1, Rgba([200, 100, 50, 128]));
+        let options = LoadOptions {
+            flip_vertically: true,
+            premultiply_alpha: true,
+            srgb: false,
+        };
+
+        let (format, data) = decode_image(DynamicImage::ImageRgba8(img), options);
+        assert_eq!(PixelFormat::Rgba8, format);
+        assert_eq!(vec![100, 50, 25, 128, 200, 100, 50, 255], data);
+    }
+
+    #[test]
+    fn test_from_raw_checks_length() {
+        let result = Texture::from_raw(3, 2, PixelFormat::Rgb8, vec![0; 17]);
+        assert!(matches!(result, Err(Error::InvalidTextureData(_))));
+        let result = Texture::from_memory(&[1, 2, 3], LoadOptions::default());
+        assert!(matches!(result, Err(Error::ImageDecode(_))));
+    }
+
+    #[test]
+    fn test_premultiply_ignores_opaque_formats() {
+        let mut data = vec![10, 20, 30];
+        premultiply_alpha(PixelFormat::Rgb8, &mut data);
+        assert_eq!(vec![10, 20, 30], data);
+    }
+}
@@ -0,0 +1,835 @@
+mod json;
+
+use crate::error::Error;
+use crate::polygons::texture::atlas::json::{parse, write_string, JsonValue};
+use crate::polygons::texture::{open_image, Filtering, LoadOptions, Texture, Wrapping};
+use image::RgbaImage;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Rectangle of a texture in pixels, `(0, 0)` is the first row of the image
+/// data. Its texture coordinates can be used directly by `Vertices`.
+#[derive(Clone)]
+pub struct TextureRegion {
+    texture: Texture,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtlasPage {
+    /// File name relative to the layout file, empty until the atlas is saved
+    pub image: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtlasEntry {
+    pub name: String,
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where every image of an atlas was placed, saved as JSON next to the pages
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AtlasLayout {
+    pub pages: Vec<AtlasPage>,
+    pub entries: Vec<AtlasEntry>,
+}
+
+/// Result of packing, before the pages are uploaded
+pub struct PackedAtlas {
+    layout: AtlasLayout,
+    images: Vec<RgbaImage>,
+}
+
+/// Pages of packed images uploaded as textures
+pub struct TextureAtlas {
+    layout: AtlasLayout,
+    pages: Vec<Texture>,
+}
+
+pub struct TextureAtlasBuilder {
+    images: Vec<(String, RgbaImage)>,
+    max_page_size: (u32, u32),
+    padding: u32,
+    extrude: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Rect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Free space of a page for the MaxRects packing algorithm
+struct MaxRects {
+    free: Vec<Rect>,
+}
+
+impl TextureRegion {
+    /// The rectangle is clamped to the size of `texture`
+    #[must_use]
+    pub fn new(texture: Texture, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let (texture_width, texture_height) = texture.get_size();
+        let x = x.min(texture_width);
+        let y = y.min(texture_height);
+        TextureRegion {
+            texture,
+            x,
+            y,
+            width: width.min(texture_width - x),
+            height: height.min(texture_height - y),
+        }
+    }
+
+    /// Shares the OpenGL texture with the region
+    #[must_use]
+    pub fn get_texture(&self) -> Texture {
+        self.texture.clone()
+    }
+
+    /// `(x, y, width, height)` in pixels
+    #[must_use]
+    pub fn get_rect(&self) -> (u32, u32, u32, u32) {
+        (self.x, self.y, self.width, self.height)
+    }
+
+    #[must_use]
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// `[u_min, v_min, u_max, v_max]`
+    #[must_use]
+    pub fn get_uv(&self) -> [f32; 4] {
+        uv_rect(
+            self.texture.get_size(),
+            Rect {
+                x: self.x,
+                y: self.y,
+                width: self.width,
+                height: self.height,
+            },
+        )
+    }
+
+    /// Maps texture coordinates of the whole texture, e.g. from
+    /// `Vertices::bounding_box_texture_coordinates`, into the region
+    #[must_use]
+    pub fn map_coordinates(&self, coordinates: &[f32]) -> Vec<f32> {
+        map_coordinates(self.get_uv(), coordinates)
+    }
+}
+
+impl AtlasLayout {
+    #[must_use]
+    pub fn get_entry(&self, name: &str) -> Option<&AtlasEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let mut result = String::from("{\n  \"pages\": [");
+        for (index, page) in self.pages.iter().enumerate() {
+            result.push_str(if index == 0 { "\n    " } else { ",\n    " });
+            result.push_str("{\"image\": ");
+            write_string(&mut result, &page.image);
+            let _ = write!(
+                result,
+                ", \"width\": {}, \"height\": {}}}",
+                page.width, page.height
+            );
+        }
+        result.push_str("\n  ],\n  \"regions\": [");
+        for (index, entry) in self.entries.iter().enumerate() {
+            result.push_str(if index == 0 { "\n    " } else { ",\n    " });
+            result.push_str("{\"name\": ");
+            write_string(&mut result, &entry.name);
+            let _ = write!(
+                result,
+                ", \"page\": {}, \"x\": {}, \"y\": {}, \"width\": {}, \"height\": {}}}",
+                entry.page, entry.x, entry.y, entry.width, entry.height
+            );
+        }
+        result.push_str("\n  ]\n}\n");
+        result
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if `json` is not a valid layout or a region lies
+    /// outside of its page
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let document = parse(json).map_err(Error::InvalidAtlasLayout)?;
+        let mut result = AtlasLayout::default();
+        for page in get_array(&document, "pages")? {
+            result.pages.push(AtlasPage {
+                image: get_field(page, "image", JsonValue::as_str)?.to_string(),
+                width: get_field(page, "width", JsonValue::as_u32)?,
+                height: get_field(page, "height", JsonValue::as_u32)?,
+            });
+        }
+        for region in get_array(&document, "regions")? {
+            let entry = AtlasEntry {
+                name: get_field(region, "name", JsonValue::as_str)?.to_string(),
+                page: get_field(region, "page", JsonValue::as_u32)? as usize,
+                x: get_field(region, "x", JsonValue::as_u32)?,
+                y: get_field(region, "y", JsonValue::as_u32)?,
+                width: get_field(region, "width", JsonValue::as_u32)?,
+                height: get_field(region, "height", JsonValue::as_u32)?,
+            };
+            let fits = result.pages.get(entry.page).is_some_and(|page| {
+                u64::from(entry.x) + u64::from(entry.width) <= u64::from(page.width)
+                    && u64::from(entry.y) + u64::from(entry.height) <= u64::from(page.height)
+            });
+            if !fits {
+                return Err(Error::InvalidAtlasLayout(format!(
+                    "region {} lies outside of page {}",
+                    entry.name, entry.page
+                )));
+            }
+            result.entries.push(entry);
+        }
+        Ok(result)
+    }
+}
+
+impl PackedAtlas {
+    #[must_use]
+    pub fn get_layout(&self) -> &AtlasLayout {
+        &self.layout
+    }
+
+    /// Page images, the first row of each image is its top
+    #[must_use]
+    pub fn get_images(&self) -> &[RgbaImage] {
+        &self.images
+    }
+
+    /// Writes the layout to `json_path` and every page next to it as
+    /// `<file stem>_<page>.png`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file could not be written
+    pub fn save(&self, json_path: &Path) -> Result<(), Error> {
+        let stem = json_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("atlas");
+        let directory = json_path.parent().unwrap_or_else(|| Path::new(""));
+        let mut layout = self.layout.clone();
+        for (index, (page, image)) in layout.pages.iter_mut().zip(&self.images).enumerate() {
+            page.image = format!("{stem}_{index}.png");
+            image
+                .save(directory.join(&page.image))
+                .map_err(|error| Error::ImageEncode(error.to_string()))?;
+        }
+        std::fs::write(json_path, layout.to_json())?;
+        Ok(())
+    }
+}
+
+impl TextureAtlas {
+    /// Uploads the pages of `packed`
+    #[must_use]
+    pub fn from_packed(packed: PackedAtlas) -> Self {
+        let pages = packed
+            .images
+            .into_iter()
+            .map(|image| upload_page(Texture::from_image(image.into(), LoadOptions::default())))
+            .collect();
+        TextureAtlas {
+            layout: packed.layout,
+            pages,
+        }
+    }
+
+    /// Pairs a layout with already loaded page textures
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the number or sizes of the pages do not match the layout
+    pub fn from_layout(layout: AtlasLayout, pages: Vec<Texture>) -> Result<Self, Error> {
+        if layout.pages.len() != pages.len() {
+            return Err(Error::InvalidAtlasLayout(format!(
+                "layout has {} pages, got {} textures",
+                layout.pages.len(),
+                pages.len()
+            )));
+        }
+        for (index, (page, texture)) in layout.pages.iter().zip(&pages).enumerate() {
+            if (page.width, page.height) != texture.get_size() {
+                return Err(Error::InvalidAtlasLayout(format!(
+                    "page {index} is {}x{}, its texture is {:?}",
+                    page.width,
+                    page.height,
+                    texture.get_size()
+                )));
+            }
+        }
+        Ok(TextureAtlas { layout, pages })
+    }
+
+    /// Loads an atlas written by `PackedAtlas::save`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the layout or a page could not be read
+    pub fn load(json_path: &Path) -> Result<Self, Error> {
+        let layout = AtlasLayout::from_json(&std::fs::read_to_string(json_path)?)?;
+        let directory = json_path.parent().unwrap_or_else(|| Path::new(""));
+        let pages = layout
+            .pages
+            .iter()
+            .map(|page| {
+                let image = open_image(&directory.join(&page.image))?;
+                Ok(upload_page(Texture::from_image(
+                    image,
+                    LoadOptions::default(),
+                )))
+            })
+            .collect::<Result<Vec<Texture>, Error>>()?;
+        TextureAtlas::from_layout(layout, pages)
+    }
+
+    #[must_use]
+    pub fn get_region(&self, name: &str) -> Option<TextureRegion> {
+        let entry = self.layout.get_entry(name)?;
+        Some(TextureRegion::new(
+            self.pages.get(entry.page)?.clone(),
+            entry.x,
+            entry.y,
+            entry.width,
+            entry.height,
+        ))
+    }
+
+    #[must_use]
+    pub fn get_layout(&self) -> &AtlasLayout {
+        &self.layout
+    }
+
+    #[must_use]
+    pub fn get_pages(&self) -> &[Texture] {
+        &self.pages
+    }
+}
+
+impl TextureAtlasBuilder {
+    /// Pages of at most 2048x2048 with 2 pixels between images
+    #[must_use]
+    pub fn new() -> Self {
+        TextureAtlasBuilder {
+            images: vec![],
+            max_page_size: (2048, 2048),
+            padding: 2,
+            extrude: 0,
+        }
+    }
+
+    /// Replaces the image previously added as `name`
+    pub fn add_image(&mut self, name: &str, image: RgbaImage) {
+        match self.images.iter_mut().find(|(current, _)| current == name) {
+            Some((_, current)) => *current = image,
+            None => self.images.push((String::from(name), image)),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the image file does not exist or could not be decoded
+    pub fn add_file(&mut self, name: &str, image_path: &Path) -> Result<(), Error> {
+        self.add_image(name, open_image(image_path)?.into_rgba8());
+        Ok(())
+    }
+
+    /// Adds an encoded image (PNG, JPEG, ...), e.g. from `include_bytes!`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data could not be decoded
+    pub fn add_encoded(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|error| Error::ImageDecode(error.to_string()))?;
+        self.add_image(name, image.into_rgba8());
+        Ok(())
+    }
+
+    pub fn set_max_page_size(&mut self, width: u32, height: u32) {
+        self.max_page_size = (width.max(1), height.max(1));
+    }
+
+    /// Empty pixels between neighbouring images
+    pub fn set_padding(&mut self, padding: u32) {
+        self.padding = padding;
+    }
+
+    /// Repeats the border pixels of every image outwards, so linear
+    /// filtering does not pick up the neighbours
+    pub fn set_extrude(&mut self, extrude: u32) {
+        self.extrude = extrude;
+    }
+
+    /// Packs the images into as few pages as needed, every page is cropped
+    /// to its contents
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an image is empty or larger than a page, or the
+    /// padding and extrusion overflow the page coordinates
+    pub fn pack(&self) -> Result<PackedAtlas, Error> {
+        let overflow = || {
+            Error::AtlasPacking(format!(
+                "padding {} and extrusion {} are too large",
+                self.padding, self.extrude
+            ))
+        };
+        let border = self
+            .extrude
+            .checked_mul(2)
+            .and_then(|extrude| extrude.checked_add(self.padding))
+            .ok_or_else(overflow)?;
+        let sizes = self
+            .images
+            .iter()
+            .map(|(name, image)| {
+                let (width, height) = image.dimensions();
+                if width == 0 || height == 0 {
+                    return Err(Error::AtlasPacking(format!("image {name} is empty")));
+                }
+                width
+                    .checked_add(border)
+                    .zip(height.checked_add(border))
+                    .ok_or_else(overflow)
+            })
+            .collect::<Result<Vec<(u32, u32)>, Error>>()?;
+
+        // the padding after the last image of a row or column is not stored
+        let bin = self
+            .max_page_size
+            .0
+            .checked_add(self.padding)
+            .zip(self.max_page_size.1.checked_add(self.padding))
+            .ok_or_else(overflow)?;
+        let placements = pack_rects(&sizes, bin).map_err(|index| {
+            let (width, height) = self.images[index].1.dimensions();
+            Error::AtlasPacking(format!(
+                "image {} of {width}x{height} does not fit in a page of {}x{}",
+                self.images[index].0, self.max_page_size.0, self.max_page_size.1
+            ))
+        })?;
+
+        let page_count = placements
+            .iter()
+            .map(|(page, _)| page + 1)
+            .max()
+            .unwrap_or(0);
+        let mut layout = AtlasLayout {
+            pages: vec![
+                AtlasPage {
+                    image: String::new(),
+                    width: 0,
+                    height: 0,
+                };
+                page_count
+            ],
+            entries: Vec::with_capacity(self.images.len()),
+        };
+        for ((name, image), (page, cell)) in self.images.iter().zip(&placements) {
+            let (width, height) = image.dimensions();
+            let size = &mut layout.pages[*page];
+            size.width = size.width.max(cell.x + cell.width - self.padding);
+            size.height = size.height.max(cell.y + cell.height - self.padding);
+            layout.entries.push(AtlasEntry {
+                name: name.clone(),
+                page: *page,
+                x: cell.x + self.extrude,
+                y: cell.y + self.extrude,
+                width,
+                height,
+            });
+        }
+
+        let mut images: Vec<RgbaImage> = layout
+            .pages
+            .iter()
+            .map(|page| RgbaImage::new(page.width, page.height))
+            .collect();
+        for ((_, image), entry) in self.images.iter().zip(&layout.entries) {
+            blit_extruded(
+                &mut images[entry.page],
+                image,
+                entry.x,
+                entry.y,
+                self.extrude,
+            );
+        }
+        Ok(PackedAtlas { layout, images })
+    }
+
+    /// Packs and uploads the pages
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an image is empty or larger than a page
+    pub fn build(&self) -> Result<TextureAtlas, Error> {
+        Ok(TextureAtlas::from_packed(self.pack()?))
+    }
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rect {
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    fn contains(&self, other: &Rect) -> bool {
+        self.x <= other.x
+            && self.y <= other.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+}
+
+impl MaxRects {
+    fn new(width: u32, height: u32) -> Self {
+        MaxRects {
+            free: vec![Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }
+    }
+
+    /// Places the rectangle in the free area that leaves the shortest side
+    /// unused
+    fn insert(&mut self, width: u32, height: u32) -> Option<Rect> {
+        let best = self
+            .free
+            .iter()
+            .filter(|free| free.width >= width && free.height >= height)
+            .min_by_key(|free| {
+                let (left_x, left_y) = (free.width - width, free.height - height);
+                (left_x.min(left_y), left_x.max(left_y))
+            })?;
+        let result = Rect {
+            x: best.x,
+            y: best.y,
+            width,
+            height,
+        };
+        self.split(&result);
+        self.prune();
+        Some(result)
+    }
+
+    /// Replaces every free area overlapping `used` by the parts around it
+    fn split(&mut self, used: &Rect) {
+        let mut result = Vec::with_capacity(self.free.len() + 4);
+        for free in &self.free {
+            if !free.intersects(used) {
+                result.push(*free);
+                continue;
+            }
+            if used.x > free.x {
+                result.push(Rect {
+                    width: used.x - free.x,
+                    ..*free
+                });
+            }
+            if used.right() < free.right() {
+                result.push(Rect {
+                    x: used.right(),
+                    width: free.right() - used.right(),
+                    ..*free
+                });
+            }
+            if used.y > free.y {
+                result.push(Rect {
+                    height: used.y - free.y,
+                    ..*free
+                });
+            }
+            if used.bottom() < free.bottom() {
+                result.push(Rect {
+                    y: used.bottom(),
+                    height: free.bottom() - used.bottom(),
+                    ..*free
+                });
+            }
+        }
+        self.free = result;
+    }
+
+    /// Removes free areas contained in another one
+    fn prune(&mut self) {
+        let free = &self.free;
+        let redundant: Vec<bool> = free
+            .iter()
+            .enumerate()
+            .map(|(index, rect)| {
+                free.iter().enumerate().any(|(other_index, other)| {
+                    other_index != index
+                        && other.contains(rect)
+                        && (other != rect || other_index < index)
+                })
+            })
+            .collect();
+        let mut redundant = redundant.into_iter();
+        self.free.retain(|_| !redundant.next().unwrap_or(false));
+    }
+}
+
+/// Places every size on the first page with room, largest first. Returns the
+/// page and rectangle in the order of `sizes`, or the index of a size that
+/// does not fit in an empty page.
+fn pack_rects(sizes: &[(u32, u32)], bin: (u32, u32)) -> Result<Vec<(usize, Rect)>, usize> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|index| {
+        let (width, height) = sizes[*index];
+        std::cmp::Reverse((width.max(height), u64::from(width) * u64::from(height)))
+    });
+
+    let mut pages: Vec<MaxRects> = vec![];
+    let mut result = vec![None; sizes.len()];
+    for index in order {
+        let (width, height) = sizes[index];
+        let placed = pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(page, rects)| rects.insert(width, height).map(|rect| (page, rect)));
+        let placed = match placed {
+            Some(placed) => placed,
+            None => {
+                let mut page = MaxRects::new(bin.0, bin.1);
+                let rect = page.insert(width, height).ok_or(index)?;
+                pages.push(page);
+                (pages.len() - 1, rect)
+            }
+        };
+        result[index] = Some(placed);
+    }
+    Ok(result.into_iter().flatten().collect())
+}
+
+/// Copies `image` to `(x, y)` of `page`, repeating its border pixels
+/// `extrude` times around it
+fn blit_extruded(page: &mut RgbaImage, image: &RgbaImage, x: u32, y: u32, extrude: u32) {
+    let (width, height) = image.dimensions();
+    for target_y in 0..height + 2 * extrude {
+        for target_x in 0..width + 2 * extrude {
+            let source_x = target_x.saturating_sub(extrude).min(width - 1);
+            let source_y = target_y.saturating_sub(extrude).min(height - 1);
+            page.put_pixel(
+                x + target_x - extrude,
+                y + target_y - extrude,
+                *image.get_pixel(source_x, source_y),
+            );
+        }
+    }
+}
+
+/// Without mipmaps, their lower levels would blend neighbouring regions
+fn upload_page(mut texture: Texture) -> Texture {
+    texture.upload();
+    texture.set_filtering(Filtering::Linear);
+    texture.set_wrapping(Wrapping::ClampToEdge);
+    texture
+}
+
+fn uv_rect(texture_size: (u32, u32), rect: Rect) -> [f32; 4] {
+    let width = texture_size.0.max(1) as f32;
+    let height = texture_size.1.max(1) as f32;
+    [
+        rect.x as f32 / width,
+        rect.y as f32 / height,
+        rect.right() as f32 / width,
+        rect.bottom() as f32 / height,
+    ]
+}
+
+fn map_coordinates(uv: [f32; 4], coordinates: &[f32]) -> Vec<f32> {
+    coordinates
+        .chunks(2)
+        .flat_map(|pair| {
+            let s = pair[0];
+            let t = pair.get(1).copied().unwrap_or(0.0);
+            [uv[0] + s * (uv[2] - uv[0]), uv[1] + t * (uv[3] - uv[1])]
+        })
+        .collect()
+}
+
+fn get_array<'a>(document: &'a JsonValue, key: &str) -> Result<&'a [JsonValue], Error> {
+    document
+        .get(key)
+        .and_then(JsonValue::as_array)
+        .ok_or_else(|| Error::InvalidAtlasLayout(format!("missing array \"{key}\"")))
+}
+
+fn get_field<'a, T>(
+    object: &'a JsonValue,
+    key: &str,
+    convert: impl FnOnce(&'a JsonValue) -> Option<T>,
+) -> Result<T, Error> {
+    object
+        .get(key)
+        .and_then(convert)
+        .ok_or_else(|| Error::InvalidAtlasLayout(format!("missing or invalid field \"{key}\"")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn filled(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn test_pack_rects_without_overlap() {
+        let sizes = [(30, 20), (10, 40), (25, 25), (15, 5), (40, 10), (8, 8)];
+        let placements = pack_rects(&sizes, (64, 64)).unwrap();
+
+        assert_eq!(sizes.len(), placements.len());
+        for (index, (page, rect)) in placements.iter().enumerate() {
+            assert_eq!(sizes[index], (rect.width, rect.height));
+            assert!(rect.right() <= 64 && rect.bottom() <= 64);
+            for (other_page, other) in &placements[index + 1..] {
+                assert!(page != other_page || !rect.intersects(other));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_rects_opens_pages_and_rejects_large() {
+        let placements = pack_rects(&[(32, 32), (32, 32)], (40, 40)).unwrap();
+        assert_eq!(
+            vec![0, 1],
+            placements.iter().map(|(page, _)| *page).collect::<Vec<_>>()
+        );
+        assert_eq!(Err(1), pack_rects(&[(8, 8), (41, 8)], (40, 40)));
+    }
+
+    #[test]
+    fn test_pack_padding_and_extrude() {
+        let mut builder = TextureAtlasBuilder::new();
+        builder.set_padding(1);
+        builder.set_extrude(1);
+        builder.add_image("big", filled(4, 4, 100));
+        builder.add_image("small", filled(2, 2, 200));
+        let packed = builder.pack().unwrap();
+        let layout = packed.get_layout();
+
+        let big = layout.get_entry("big").unwrap();
+        assert_eq!(
+            (0, 1, 1, 4, 4),
+            (big.page, big.x, big.y, big.width, big.height)
+        );
+        let small = layout.get_entry("small").unwrap();
+        assert!(small.x >= big.x + big.width + 3 || small.y >= big.y + big.height + 3);
+
+        let page = &packed.get_images()[0];
+        assert_eq!(
+            (layout.pages[0].width, layout.pages[0].height),
+            page.dimensions()
+        );
+        assert_eq!(&Rgba([100, 100, 100, 255]), page.get_pixel(0, 0));
+        assert_eq!(
+            &Rgba([200, 200, 200, 255]),
+            page.get_pixel(small.x - 1, small.y)
+        );
+    }
+
+    #[test]
+    fn test_pack_rejects_empty_and_oversized() {
+        let mut builder = TextureAtlasBuilder::new();
+        builder.set_max_page_size(16, 16);
+        builder.add_image("wide", filled(20, 2, 0));
+        assert!(matches!(builder.pack(), Err(Error::AtlasPacking(_))));
+
+        let mut builder = TextureAtlasBuilder::new();
+        builder.add_image("empty", RgbaImage::new(0, 3));
+        assert!(matches!(builder.pack(), Err(Error::AtlasPacking(_))));
+    }
+
+    #[test]
+    fn test_pack_rejects_overflowing_border() {
+        let mut builder = TextureAtlasBuilder::new();
+        builder.add_image("small", filled(2, 2, 0));
+        builder.set_extrude(u32::MAX / 2);
+        assert!(matches!(builder.pack(), Err(Error::AtlasPacking(_))));
+
+        builder.set_extrude(0);
+        builder.set_padding(u32::MAX);
+        assert!(matches!(builder.pack(), Err(Error::AtlasPacking(_))));
+    }
+
+    #[test]
+    fn test_layout_json_round_trip() {
+        let mut builder = TextureAtlasBuilder::new();
+        builder.add_image("a \"quoted\" name", filled(3, 5, 1));
+        builder.add_image("b", filled(7, 2, 2));
+        let mut layout = builder.pack().unwrap().get_layout().clone();
+        layout.pages[0].image = String::from("atlas_0.png");
+
+        assert_eq!(layout, AtlasLayout::from_json(&layout.to_json()).unwrap());
+    }
+
+    #[test]
+    fn test_layout_json_validation() {
+        let outside = r#"{"pages": [{"image": "", "width": 4, "height": 4}],
+            "regions": [{"name": "a", "page": 0, "x": 2, "y": 0, "width": 3, "height": 1}]}"#;
+        assert!(matches!(
+            AtlasLayout::from_json(outside),
+            Err(Error::InvalidAtlasLayout(_))
+        ));
+        assert!(AtlasLayout::from_json(r#"{"pages": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_region_coordinates() {
+        let uv = uv_rect(
+            (64, 32),
+            Rect {
+                x: 16,
+                y: 8,
+                width: 32,
+                height: 16,
+            },
+        );
+        assert_eq!([0.25, 0.25, 0.75, 0.75], uv);
+        assert_eq!(
+            vec![0.25, 0.25, 0.75, 0.5],
+            map_coordinates(uv, &[0.0, 0.0, 1.0, 0.5])
+        );
+    }
+}
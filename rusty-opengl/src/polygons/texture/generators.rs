@@ -0,0 +1,180 @@
+//! Procedural images, turn them into textures with `Texture::from_image`
+
+use crate::color::RGBA;
+use image::{Rgba, RgbaImage};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GradientDirection {
+    /// From the left edge to the right edge
+    Horizontal,
+    /// From the top row to the bottom row
+    Vertical,
+    /// From the center to the corners
+    Radial,
+}
+
+#[must_use]
+pub fn solid_color(width: u32, height: u32, color: &RGBA) -> RgbaImage {
+    RgbaImage::from_pixel(width, height, to_pixel(color))
+}
+
+/// Squares of `cell_size` pixels, `first` in the top-left corner
+#[must_use]
+pub fn checkerboard(
+    width: u32,
+    height: u32,
+    cell_size: u32,
+    first: &RGBA,
+    second: &RGBA,
+) -> RgbaImage {
+    let cell_size = cell_size.max(1);
+    let (first, second) = (to_pixel(first), to_pixel(second));
+    RgbaImage::from_fn(width, height, |x, y| {
+        if (x / cell_size + y / cell_size).is_multiple_of(2) {
+            first
+        } else {
+            second
+        }
+    })
+}
+
+#[must_use]
+pub fn gradient(
+    width: u32,
+    height: u32,
+    from: &RGBA,
+    to: &RGBA,
+    direction: GradientDirection,
+) -> RgbaImage {
+    let (from, to) = (to_pixel(from), to_pixel(to));
+    let progress = |value: u32, size: u32| {
+        if size > 1 {
+            value as f32 / (size - 1) as f32
+        } else {
+            0.0
+        }
+    };
+    let center = (width as f32 / 2.0, height as f32 / 2.0);
+    let max_distance = center.0.hypot(center.1).max(f32::EPSILON);
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let t = match direction {
+            GradientDirection::Horizontal => progress(x, width),
+            GradientDirection::Vertical => progress(y, height),
+            GradientDirection::Radial => {
+                let distance = (x as f32 + 0.5 - center.0).hypot(y as f32 + 0.5 - center.1);
+                (distance / max_distance).min(1.0)
+            }
+        };
+        mix(from, to, t)
+    })
+}
+
+/// Opaque grayscale value noise, `cell_size` is the distance in pixels
+/// between random values. The same seed gives the same image.
+#[must_use]
+pub fn noise(width: u32, height: u32, cell_size: f32, seed: u32) -> RgbaImage {
+    let cell_size = cell_size.max(1.0);
+    RgbaImage::from_fn(width, height, |x, y| {
+        let value = value_noise(
+            (x as f32 + 0.5) / cell_size,
+            (y as f32 + 0.5) / cell_size,
+            seed,
+        );
+        let gray = (value * 255.0).round() as u8;
+        Rgba([gray, gray, gray, 255])
+    })
+}
+
+fn to_pixel(color: &RGBA) -> Rgba<u8> {
+    let (red, green, blue, alpha) = color.get_rgba();
+    Rgba([red, green, blue, (alpha * 255.0).round() as u8])
+}
+
+fn mix(from: Rgba<u8>, to: Rgba<u8>, t: f32) -> Rgba<u8> {
+    let mut result = from;
+    for (channel, target) in result.0.iter_mut().zip(to.0) {
+        let value = f32::from(*channel) + (f32::from(target) - f32::from(*channel)) * t;
+        *channel = value.round() as u8;
+    }
+    result
+}
+
+/// Smoothly interpolated random values at integer coordinates, in [0, 1]
+fn value_noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (cell_x, cell_y) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - cell_x), smooth(y - cell_y));
+    let (cell_x, cell_y) = (cell_x as i32, cell_y as i32);
+
+    let top = lerp(
+        hash(cell_x, cell_y, seed),
+        hash(cell_x + 1, cell_y, seed),
+        tx,
+    );
+    let bottom = lerp(
+        hash(cell_x, cell_y + 1, seed),
+        hash(cell_x + 1, cell_y + 1, seed),
+        tx,
+    );
+    lerp(top, bottom, ty)
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// Integer hash of the coordinates mapped to [0, 1]
+fn hash(x: i32, y: i32, seed: u32) -> f32 {
+    let mut result =
+        seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1);
+    result = (result ^ (result >> 15)).wrapping_mul(0x85eb_ca6b);
+    result = (result ^ (result >> 13)).wrapping_mul(0xc2b2_ae35);
+    result ^= result >> 16;
+    (result & 0xFFFF) as f32 / 65535.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkerboard_cells() {
+        let white = RGBA::from_hex(0xFF_FF_FF_FF);
+        let black = RGBA::from_hex(0x00_00_00_FF);
+        let result = checkerboard(4, 4, 2, &white, &black);
+
+        assert_eq!(&Rgba([255, 255, 255, 255]), result.get_pixel(1, 1));
+        assert_eq!(&Rgba([0, 0, 0, 255]), result.get_pixel(2, 1));
+        assert_eq!(&Rgba([255, 255, 255, 255]), result.get_pixel(3, 3));
+    }
+
+    #[test]
+    fn test_gradient_ends() {
+        let red = RGBA::from_hex(0xFF_00_00_FF);
+        let blue = RGBA::from_hex(0x00_00_FF_00);
+        let result = gradient(3, 2, &red, &blue, GradientDirection::Horizontal);
+
+        assert_eq!(&Rgba([255, 0, 0, 255]), result.get_pixel(0, 1));
+        assert_eq!(&Rgba([128, 0, 128, 128]), result.get_pixel(1, 0));
+        assert_eq!(&Rgba([0, 0, 255, 0]), result.get_pixel(2, 1));
+    }
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        let first = noise(16, 16, 4.0, 7);
+        assert_eq!(first, noise(16, 16, 4.0, 7));
+        assert_ne!(first, noise(16, 16, 4.0, 8));
+        assert!(first
+            .pixels()
+            .all(|pixel| pixel[0] == pixel[1] && pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_solid_color() {
+        let result = solid_color(2, 2, &RGBA::new(10, 20, 30, 0.5));
+        assert!(result
+            .pixels()
+            .all(|pixel| *pixel == Rgba([10, 20, 30, 128])));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::polygons::texture::Texture;
+use crate::shaders::shader_program::ShaderProgram;
+use crate::shaders::uniform::Sampler;
+use std::cell::RefCell;
+
+/// Remembers which texture is bound to every texture unit, so drawing the
+/// same textures again does not rebind them.
+///
+/// Every texture binding of the library goes through the allocator of the
+/// current thread, see `with_texture_units`. Call `invalidate` after binding
+/// textures with raw `gl` calls.
+#[derive(Clone, Debug)]
+pub struct TextureUnits {
+    bound: Vec<Option<u32>>,
+    last_used: Vec<u64>,
+    tick: u64,
+}
+
+thread_local! {
+    static TEXTURE_UNITS: RefCell<TextureUnits> = RefCell::new(TextureUnits::new(0));
+}
+
+impl TextureUnits {
+    #[must_use]
+    pub fn new(count: usize) -> Self {
+        TextureUnits {
+            bound: vec![None; count],
+            last_used: vec![0; count],
+            tick: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn get_count(&self) -> usize {
+        self.bound.len()
+    }
+
+    /// Texture bound to `unit` as far as the allocator knows
+    #[must_use]
+    pub fn get_bound(&self, unit: u32) -> Option<u32> {
+        self.bound.get(unit as usize).copied().flatten()
+    }
+
+    /// Binds every texture to its own unit, reusing units that already hold
+    /// it. Returns the units in order, `None` for textures that did not fit.
+    /// The unit of the last texture is left active.
+    pub fn bind(&mut self, textures: &[&Texture]) -> Vec<Option<u32>> {
+        let ids: Vec<u32> = textures.iter().map(|texture| texture.get_id()).collect();
+        let assignments = self.assign(&ids);
+        for (id, assignment) in ids.iter().zip(&assignments) {
+            let Some((unit, needs_bind)) = assignment else {
+                continue;
+            };
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                if *needs_bind {
+                    gl::BindTexture(gl::TEXTURE_2D, *id);
+                }
+            }
+        }
+        assignments
+            .into_iter()
+            .map(|assignment| assignment.map(|(unit, _)| unit))
+            .collect()
+    }
+
+    /// Forgets all bindings, the next `bind` binds every texture again
+    pub fn invalidate(&mut self) {
+        self.bound.fill(None);
+    }
+
+    /// Called when a texture is deleted, OpenGL may reuse its name
+    fn forget(&mut self, texture_id: u32) {
+        for bound in &mut self.bound {
+            if *bound == Some(texture_id) {
+                *bound = None;
+            }
+        }
+    }
+
+    /// Chooses a unit for every id and whether it has to be bound. Units
+    /// used by earlier ids of the same call are never taken, otherwise the
+    /// least recently used unit is replaced.
+    fn assign(&mut self, ids: &[u32]) -> Vec<Option<(u32, bool)>> {
+        self.tick += 1;
+        let mut taken = vec![false; self.bound.len()];
+        let mut result = Vec::with_capacity(ids.len());
+        for id in ids {
+            let existing = self.bound.iter().position(|bound| *bound == Some(*id));
+            let assignment = match existing {
+                Some(unit) => Some((unit, false)),
+                None => (0..self.bound.len())
+                    .filter(|unit| !taken[*unit])
+                    .min_by_key(|unit| (self.bound[*unit].is_some(), self.last_used[*unit]))
+                    .map(|unit| (unit, true)),
+            };
+
+            if let Some((unit, _)) = assignment {
+                taken[unit] = true;
+                self.bound[unit] = Some(*id);
+                self.last_used[unit] = self.tick;
+            }
+            result.push(
+                assignment.map(|(unit, needs_bind)| {
+                    (u32::try_from(unit).unwrap_or(u32::MAX), needs_bind)
+                }),
+            );
+        }
+        result
+    }
+}
+
+/// Runs `f` with the allocator of the current thread, the number of units is
+/// queried from the driver on the first use
+pub fn with_texture_units<R>(f: impl FnOnce(&mut TextureUnits) -> R) -> R {
+    TEXTURE_UNITS.with(|units| {
+        let mut units = units.borrow_mut();
+        if units.get_count() == 0 {
+            let mut count = 0;
+            unsafe {
+                gl::GetIntegerv(gl::MAX_TEXTURE_IMAGE_UNITS, &mut count);
+            }
+            *units = TextureUnits::new(usize::try_from(count).unwrap_or(0).max(1));
+        }
+        f(&mut units)
+    })
+}
+
+/// Binds the textures and points the sampler uniform named next to each of
+/// them at its unit
+///
+/// # Panics
+///
+/// Will panic if `shader` has no active uniform with one of the names, check
+/// them once with `ShaderProgram::require_uniforms` when the program is built
+pub fn bind_samplers<'a>(
+    shader: &ShaderProgram,
+    textures: impl IntoIterator<Item = (&'a str, &'a Texture)>,
+) {
+    let (names, textures): (Vec<&str>, Vec<&Texture>) = textures.into_iter().unzip();
+    let units = with_texture_units(|units| units.bind(&textures));
+    for (name, unit) in names.into_iter().zip(units) {
+        if let Some(unit) = unit {
+            if let Err(error) = shader.set_uniform(name, &Sampler(unit)) {
+                panic!("unchecked sampler: {error}");
+            }
+        }
+    }
+}
+
+pub(crate) fn forget_texture(texture_id: u32) {
+    // the allocator may already be gone when the thread exits
+    let _ = TEXTURE_UNITS.try_with(|units| {
+        if let Ok(mut units) = units.try_borrow_mut() {
+            units.forget(texture_id);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_reuses_bound_units() {
+        let mut units = TextureUnits::new(4);
+        assert_eq!(
+            vec![Some((0, true)), Some((1, true))],
+            units.assign(&[10, 20])
+        );
+        assert_eq!(
+            vec![Some((1, false)), Some((0, false)), Some((2, true))],
+            units.assign(&[20, 10, 30])
+        );
+        assert_eq!(Some(30), units.get_bound(2));
+    }
+
+    #[test]
+    fn test_assign_replaces_least_recently_used() {
+        let mut units = TextureUnits::new(2);
+        units.assign(&[1]);
+        units.assign(&[2]);
+        units.assign(&[1]);
+        assert_eq!(vec![Some((1, true))], units.assign(&[3]));
+        assert_eq!(
+            vec![Some((0, false)), Some((1, true)), None],
+            units.assign(&[1, 4, 5])
+        );
+    }
+
+    #[test]
+    fn test_forget_and_invalidate() {
+        let mut units = TextureUnits::new(2);
+        units.assign(&[7, 8]);
+        units.forget(7);
+        assert_eq!(None, units.get_bound(0));
+        assert_eq!(vec![Some((1, false))], units.assign(&[8]));
+
+        units.invalidate();
+        assert_eq!(vec![Some((0, true))], units.assign(&[8]));
+    }
+}
@@ -0,0 +1,236 @@
+/// Triangulates a simple polygon (convex or concave, without holes) given as
+/// an outline of `[x, y]` points, using ear clipping.
+///
+/// Returns indices into `points`, three per triangle, all wound counter-clockwise.
+/// Returns `None` for fewer than 3 points, polygons without area and
+/// outlines with crossing edges. Tolerances scale with the bounding box, so
+/// the result does not depend on the units of the points.
+#[must_use]
+pub fn ear_clipping(points: &[[f32; 2]]) -> Option<Vec<u32>> {
+    if points.len() < 3 {
+        return None;
+    }
+    let tolerance = get_tolerance(points);
+    if signed_area(points).abs() <= tolerance || has_crossing_edges(points, tolerance) {
+        return None;
+    }
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut result = Vec::with_capacity((points.len() - 2) * 3);
+    while remaining.len() > 3 {
+        let ear = find_ear(points, &remaining, tolerance)?;
+        let count = remaining.len();
+        let (prev, cur, next) = (
+            remaining[(ear + count - 1) % count],
+            remaining[ear],
+            remaining[(ear + 1) % count],
+        );
+
+        if cross(points[prev], points[cur], points[next]).abs() > tolerance {
+            result.extend([prev, cur, next].map(|index| u32::try_from(index).unwrap()));
+        }
+        remaining.remove(ear);
+    }
+
+    if cross(
+        points[remaining[0]],
+        points[remaining[1]],
+        points[remaining[2]],
+    )
+    .abs()
+        > tolerance
+    {
+        result.extend(remaining.iter().map(|index| u32::try_from(*index).unwrap()));
+    }
+
+    Some(result)
+}
+
+/// Index of a vertex in `remaining` that can be cut off, collinear vertices
+/// are returned as well so they get dropped without producing a triangle
+fn find_ear(points: &[[f32; 2]], remaining: &[usize], tolerance: f32) -> Option<usize> {
+    let count = remaining.len();
+    for i in 0..count {
+        let prev = remaining[(i + count - 1) % count];
+        let cur = remaining[i];
+        let next = remaining[(i + 1) % count];
+        let turn = cross(points[prev], points[cur], points[next]);
+
+        if turn.abs() <= tolerance {
+            return Some(i);
+        }
+        if turn < 0.0 {
+            continue;
+        }
+
+        let is_ear = remaining
+            .iter()
+            .filter(|index| ![prev, cur, next].contains(index))
+            .map(|index| points[*index])
+            .filter(|point| ![points[prev], points[cur], points[next]].contains(point))
+            .all(|point| !is_in_triangle(point, points[prev], points[cur], points[next]));
+        if is_ear {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Threshold for areas and cross products, relative to the squared size of
+/// the bounding box
+fn get_tolerance(points: &[[f32; 2]]) -> f32 {
+    let (mut min, mut max) = (points[0], points[0]);
+    for point in points {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    let extent = (max[0] - min[0]).max(max[1] - min[1]);
+    extent * extent * f32::EPSILON
+}
+
+/// `true` if two edges that do not share a vertex cross each other
+fn has_crossing_edges(points: &[[f32; 2]], tolerance: f32) -> bool {
+    let count = points.len();
+    let edge = |i: usize| (points[i], points[(i + 1) % count]);
+    (0..count).any(|i| {
+        (i + 2..count).filter(|j| (j + 1) % count != i).any(|j| {
+            let ((a, b), (c, d)) = (edge(i), edge(j));
+            are_on_opposite_sides(cross(a, b, c), cross(a, b, d), tolerance)
+                && are_on_opposite_sides(cross(c, d, a), cross(c, d, b), tolerance)
+        })
+    })
+}
+
+fn are_on_opposite_sides(first: f32, second: f32, tolerance: f32) -> bool {
+    (first > tolerance && second < -tolerance) || (first < -tolerance && second > tolerance)
+}
+
+fn signed_area(points: &[[f32; 2]]) -> f32 {
+    let mut result = 0.0;
+    for (i, current) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        result += current[0] * next[1] - next[0] * current[1];
+    }
+    result / 2.0
+}
+
+fn cross(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn is_in_triangle(point: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    cross(a, b, point) >= 0.0 && cross(b, c, point) >= 0.0 && cross(c, a, point) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangles_area(points: &[[f32; 2]], indices: &[u32]) -> f32 {
+        indices
+            .chunks(3)
+            .map(|triangle| {
+                cross(
+                    points[triangle[0] as usize],
+                    points[triangle[1] as usize],
+                    points[triangle[2] as usize],
+                ) / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_ear_clipping_too_few_points() {
+        assert!(ear_clipping(&[[0.0, 0.0], [1.0, 0.0]]).is_none());
+    }
+
+    #[test]
+    fn test_ear_clipping_no_area() {
+        assert!(ear_clipping(&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]).is_none());
+    }
+
+    #[test]
+    fn test_ear_clipping_triangle() {
+        let result = ear_clipping(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]).unwrap();
+        assert_eq!(vec![0, 1, 2], result);
+    }
+
+    #[test]
+    fn test_ear_clipping_clockwise_rectangle() {
+        let points = [[0.5, 0.5], [0.5, -0.5], [-0.5, -0.5], [-0.5, 0.5]];
+        let result = ear_clipping(&points).unwrap();
+        assert_eq!(6, result.len());
+        assert!((triangles_area(&points, &result) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ear_clipping_pentagon() {
+        let points = [
+            [0.0, 1.0],
+            [-1.0, 0.3],
+            [-0.6, -0.8],
+            [0.6, -0.8],
+            [1.0, 0.3],
+        ];
+        let result = ear_clipping(&points).unwrap();
+        assert_eq!(9, result.len());
+        assert!((triangles_area(&points, &result) - signed_area(&points).abs()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_ear_clipping_concave_l_shape() {
+        let points = [
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ];
+        let result = ear_clipping(&points).unwrap();
+        assert_eq!(12, result.len());
+        assert!((triangles_area(&points, &result) - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ear_clipping_skips_collinear_points() {
+        let points = [[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]];
+        let result = ear_clipping(&points).unwrap();
+        assert!((triangles_area(&points, &result) - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ear_clipping_self_intersecting() {
+        let bow_tie = [[0.0, 0.0], [2.0, 2.0], [2.0, 0.0], [0.0, 2.0]];
+        assert!(ear_clipping(&bow_tie).is_none());
+
+        // crossing edges around a non-zero signed area
+        let uneven_bow_tie = [[0.0, 0.0], [2.0, 2.0], [2.0, 0.0], [0.0, 1.0]];
+        assert!(signed_area(&uneven_bow_tie).abs() > 0.5);
+        assert!(ear_clipping(&uneven_bow_tie).is_none());
+    }
+
+    #[test]
+    fn test_ear_clipping_small_polygons() {
+        let triangle = [[0.0, 0.0], [1e-4, 0.0], [0.0, 1e-4]];
+        assert_eq!(vec![0, 1, 2], ear_clipping(&triangle).unwrap());
+
+        let points = [
+            [0.0, 0.0],
+            [2e-4, 0.0],
+            [2e-4, 1e-4],
+            [1e-4, 1e-4],
+            [1e-4, 2e-4],
+            [0.0, 2e-4],
+        ];
+        let result = ear_clipping(&points).unwrap();
+        assert_eq!(12, result.len());
+        assert!((triangles_area(&points, &result) - 3e-8).abs() < 1e-12);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::polygons::layout::{AttributeType, VertexLayout};
+use crate::polygons::triangulation::ear_clipping;
 use crate::polygons::RGBA;
 
 #[derive(Copy, Clone)]
@@ -47,6 +49,28 @@ impl Vertices {
         }
     }
 
+    /// Overwrites the data of vertices starting at `first_vertex`, returns
+    /// `false` if the data does not fit in the existing vertices
+    pub fn set_position_range(
+        &mut self,
+        first_vertex: usize,
+        position: &[f32],
+        location: VertexLocation,
+    ) -> bool {
+        let (data, components) = match location {
+            VertexLocation::Position => (&mut self.vert_pos, 3),
+            VertexLocation::Color => (&mut self.colors_pos, 4),
+            VertexLocation::Texture => (&mut self.textures_pos, 2),
+        };
+
+        let start = first_vertex * components;
+        if !position.len().is_multiple_of(components) || start + position.len() > data.len() {
+            return false;
+        }
+        data[start..start + position.len()].copy_from_slice(position);
+        true
+    }
+
     pub fn append_colors(&mut self, colors: &[RGBA]) {
         for color in colors.iter() {
             self.colors_pos
@@ -73,56 +97,100 @@ impl Vertices {
         self.vert_pos.len() == 12
     }
 
+    #[must_use]
+    pub fn get_vertices_count(&self) -> usize {
+        self.vert_pos.len() / 3
+    }
+
+    /// Triangulates the outline formed by the positions projected onto the XY
+    /// plane, returns indices suitable for an element buffer
+    #[must_use]
+    pub fn triangulate(&self) -> Option<Vec<u32>> {
+        if !self.vert_pos.len().is_multiple_of(3) {
+            return None;
+        }
+
+        let outline: Vec<[f32; 2]> = self
+            .vert_pos
+            .chunks(3)
+            .map(|position| [position[0], position[1]])
+            .collect();
+        ear_clipping(&outline)
+    }
+
+    /// Texture coordinates stretching a texture over the bounding box of the
+    /// positions in the XY plane
+    #[must_use]
+    pub fn bounding_box_texture_coordinates(&self) -> Vec<f32> {
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for position in self.vert_pos.chunks(3) {
+            min_x = min_x.min(position[0]);
+            min_y = min_y.min(position[1]);
+            max_x = max_x.max(position[0]);
+            max_y = max_y.max(position[1]);
+        }
+
+        let width = (max_x - min_x).max(f32::EPSILON);
+        let height = (max_y - min_y).max(f32::EPSILON);
+        let mut result = Vec::with_capacity(self.get_vertices_count() * 2);
+        for position in self.vert_pos.chunks(3) {
+            result.push((position[0] - min_x) / width);
+            result.push((position[1] - min_y) / height);
+        }
+        result
+    }
+
     #[must_use]
     pub fn create_single_vertices_array(&self) -> Option<Vec<f32>> {
-        if !self.is_valid_structure() {
+        self.create_vertices_range(0, self.get_vertices_count())
+    }
+
+    /// Interleaved data of `count` vertices starting at `first_vertex`, in the
+    /// layout of `create_single_vertices_array`
+    #[must_use]
+    pub fn create_vertices_range(&self, first_vertex: usize, count: usize) -> Option<Vec<f32>> {
+        if !self.is_valid_structure() || first_vertex + count > self.get_vertices_count() {
             return None;
         }
 
-        let mut result = Vec::with_capacity(self.sum_capacity());
         let is_texture = !self.textures_pos.is_empty();
         let is_color = !self.colors_pos.is_empty();
-        let vertices = self.vert_pos.len() / 3;
-
-        let mut vert_pos_index = 0;
-        let mut color_pos_index = 0;
-        let mut texture_pos_index = 0;
-        for _ in 0..vertices {
-            for _ in 0..3 {
-                result.push(self.vert_pos[vert_pos_index]);
-                vert_pos_index += 1;
-            }
-
+        let floats_per_vertex = self.get_layout().get_stride() / std::mem::size_of::<f32>();
+        let mut result = Vec::with_capacity(count * floats_per_vertex);
+        for vertex in first_vertex..first_vertex + count {
+            result.extend_from_slice(&self.vert_pos[vertex * 3..vertex * 3 + 3]);
             if is_color {
-                for _ in 0..4 {
-                    result.push(self.colors_pos[color_pos_index]);
-                    color_pos_index += 1;
-                }
+                result.extend_from_slice(&self.colors_pos[vertex * 4..vertex * 4 + 4]);
             }
-
             if is_texture {
-                for _ in 0..2 {
-                    result.push(self.textures_pos[texture_pos_index]);
-                    texture_pos_index += 1;
-                }
+                result.extend_from_slice(&self.textures_pos[vertex * 2..vertex * 2 + 2]);
             }
         }
 
         Some(result)
     }
 
+    /// # Panics
+    ///
+    /// Will panic if the stride does not fit in `i32`
     #[must_use]
     pub fn get_stride(&self) -> i32 {
-        let mut result = 3;
+        self.get_layout().get_stride().try_into().unwrap()
+    }
+
+    /// Layout of the array built by `create_single_vertices_array`, matching
+    /// the attribute locations of the built-in shaders
+    #[must_use]
+    pub fn get_layout(&self) -> VertexLayout {
+        let mut result = VertexLayout::new().with_attribute("aPos", 0, 3, AttributeType::F32);
         if !self.colors_pos.is_empty() {
-            result = 7;
+            result.push_attribute("aColor", 1, 4, AttributeType::F32);
         }
-
         if !self.textures_pos.is_empty() {
-            result = 9;
+            result.push_attribute("aTexCoord", 2, 2, AttributeType::F32);
         }
-
-        (result * std::mem::size_of::<f32>()) as i32
+        result
     }
 
     #[must_use]
@@ -193,6 +261,21 @@ mod tests {
         assert_eq!(9, size);
     }
 
+    #[test]
+    fn test_create_vertices_range() {
+        let vertices = Vertices::new(
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0],
+            vec![],
+        );
+        let all = vertices.create_single_vertices_array().unwrap();
+        assert_eq!(
+            Some(all[7..21].to_vec()),
+            vertices.create_vertices_range(1, 2)
+        );
+        assert_eq!(None, vertices.create_vertices_range(2, 2));
+    }
+
     #[test]
     fn test_create_vertices_only_positions() {
         let mut vertices = Vertices::empty();
@@ -259,6 +342,47 @@ mod tests {
         assert_eq!(12, result);
     }
 
+    #[test]
+    fn test_get_layout_with_texture() {
+        let vertices = Vertices::new(
+            vec![0.5, 0.5, 0.0],
+            vec![1.0, 0.5, 0.0, 1.0],
+            vec![1.0, 1.0],
+        );
+        let layout = vertices.get_layout();
+        assert_eq!(3, layout.get_attributes().len());
+        assert_eq!(28, layout.get_offset(2));
+        assert_eq!(36, vertices.get_stride());
+    }
+
+    #[test]
+    fn test_get_layout_texture_without_colors() {
+        let vertices = Vertices::new(vec![0.5, 0.5, 0.0], vec![], vec![1.0, 1.0]);
+        let layout = vertices.get_layout();
+        assert_eq!(2, layout.get_attribute("aTexCoord").unwrap().location);
+        assert_eq!(12, layout.get_offset(1));
+        assert_eq!(20, vertices.get_stride());
+    }
+
+    #[test]
+    fn test_set_position_range() {
+        let mut vertices = Vertices::empty();
+        let pos = vec![-0.9, 0.0, 0.0, -0.5, 0.5, 0.0, -0.5, 0.0, 0.0];
+        vertices.set_position(&pos, VertexLocation::Position);
+
+        assert!(vertices.set_position_range(1, &[0.1, 0.2, 0.3], VertexLocation::Position));
+        assert_eq!(
+            vec![-0.9, 0.0, 0.0, 0.1, 0.2, 0.3, -0.5, 0.0, 0.0],
+            vertices.create_single_vertices_array().unwrap()
+        );
+        assert!(!vertices.set_position_range(
+            2,
+            &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6],
+            VertexLocation::Position
+        ));
+        assert!(!vertices.set_position_range(0, &[0.1, 0.2], VertexLocation::Position));
+    }
+
     #[test]
     fn test_append_colors() {
         let mut vertices = Vertices::empty();
@@ -273,4 +397,36 @@ mod tests {
         assert_eq!(12, vertices.sum_capacity());
         assert_eq!(1_f32.to_bits(), vertices.colors_pos[10].to_bits());
     }
+
+    #[test]
+    fn test_triangulate_pentagon() {
+        let mut vertices = Vertices::empty();
+        let pos = vec![
+            0.0, 0.8, 0.0, -0.8, 0.2, 0.0, -0.5, -0.7, 0.0, 0.5, -0.7, 0.0, 0.8, 0.2, 0.0,
+        ];
+        vertices.set_position(&pos, VertexLocation::Position);
+        assert_eq!(5, vertices.get_vertices_count());
+        assert_eq!(9, vertices.triangulate().unwrap().len());
+    }
+
+    #[test]
+    fn test_triangulate_invalid_positions() {
+        let vertices = Vertices::new(vec![0.0, 0.8, 0.0, -0.8], vec![], vec![]);
+        assert!(vertices.triangulate().is_none());
+    }
+
+    #[test]
+    fn test_bounding_box_texture_coordinates() {
+        let vertices = Vertices::new(
+            vec![
+                0.5, 0.5, 0.0, 0.5, -0.5, 0.0, -0.5, -0.5, 0.0, -0.5, 0.5, 0.0,
+            ],
+            vec![],
+            vec![],
+        );
+        assert_eq!(
+            vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            vertices.bounding_box_texture_coordinates()
+        );
+    }
 }
@@ -0,0 +1,284 @@
+pub mod effect;
+
+use crate::error::Error;
+use crate::gl_state::{
+    bind_framebuffer, get_capabilities, get_framebuffer, set_capabilities, Capabilities,
+};
+use crate::polygons::texture::Texture;
+use crate::post_process::effect::{Effect, Pass};
+use crate::render_target::{RenderTarget, RenderTargetBuilder};
+
+/// Chain of fullscreen effects applied to the scene.
+///
+/// Draw the scene between `begin` and `end`, `end` runs the enabled effects
+/// in order and draws the result into the window. Intermediate results go to
+/// offscreen targets: two are ping-ponged between passes and a third keeps
+/// the input of effects that read it again in a later pass (e.g. bloom).
+pub struct PostProcess {
+    scene: RenderTarget,
+    targets: [RenderTarget; 3],
+    effects: Vec<Effect>,
+    copy: Effect,
+    vertex_array: u32,
+}
+
+/// Where a pass reads its inputs from and writes to, `None` is the input of
+/// the chain for reads and its output for writes
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct PassPlan {
+    source: Option<usize>,
+    original: Option<usize>,
+    target: Option<usize>,
+}
+
+impl PostProcess {
+    /// `samples` is the number of MSAA samples of the scene, 0 disables
+    /// multisampling
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the copy shader could not be loaded or a target
+    /// could not be created
+    pub fn new(width: u32, height: u32, samples: u32) -> Result<Self, Error> {
+        let mut vertex_array = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array);
+        }
+        Ok(PostProcess {
+            scene: build_scene_target(width, height, samples)?,
+            targets: build_targets(width, height)?,
+            effects: vec![],
+            copy: Effect::new("copy", vec![Pass::new("post_copy.frag")?]),
+            vertex_array,
+        })
+    }
+
+    /// Appends `effect` to the end of the chain
+    pub fn push_effect(&mut self, effect: Effect) {
+        self.effects.push(effect);
+    }
+
+    /// Removes the first effect called `name`
+    pub fn remove_effect(&mut self, name: &str) -> Option<Effect> {
+        let index = self
+            .effects
+            .iter()
+            .position(|effect| effect.get_name() == name)?;
+        Some(self.effects.remove(index))
+    }
+
+    #[must_use]
+    pub fn get_effect(&self, name: &str) -> Option<&Effect> {
+        self.effects.iter().find(|effect| effect.get_name() == name)
+    }
+
+    pub fn get_effect_mut(&mut self, name: &str) -> Option<&mut Effect> {
+        self.effects
+            .iter_mut()
+            .find(|effect| effect.get_name() == name)
+    }
+
+    #[must_use]
+    pub fn get_effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    /// Target the scene is drawn into between `begin` and `end`
+    #[must_use]
+    pub fn get_scene(&self) -> &RenderTarget {
+        &self.scene
+    }
+
+    #[must_use]
+    pub fn get_size(&self) -> (u32, u32) {
+        self.scene.get_size()
+    }
+
+    /// Recreates the targets when the size changed, call it with the new
+    /// framebuffer size after `Event::Resized`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a target could not be created
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Error> {
+        if self.get_size() == (width, height) {
+            return Ok(());
+        }
+        self.scene = build_scene_target(width, height, self.scene.get_samples())?;
+        self.targets = build_targets(width, height)?;
+        Ok(())
+    }
+
+    /// Redirects drawing into the scene target
+    pub fn begin(&self) {
+        self.scene.bind();
+    }
+
+    /// Runs the effects on the scene and draws the result into the window
+    pub fn end(&self) {
+        self.scene.unbind();
+        self.apply(&self.scene.get_textures()[0], None);
+    }
+
+    /// Runs the enabled effects on `input` and draws the result into
+    /// `output`, or into the window when it is `None`. Without enabled
+    /// effects the input is copied.
+    pub fn apply(&self, input: &Texture, output: Option<&RenderTarget>) {
+        let mut effects: Vec<&Effect> = self
+            .effects
+            .iter()
+            .filter(|effect| effect.is_enabled() && !effect.get_passes().is_empty())
+            .collect();
+        if effects.is_empty() {
+            effects.push(&self.copy);
+        }
+
+        let pass_counts: Vec<usize> = effects
+            .iter()
+            .map(|effect| effect.get_passes().len())
+            .collect();
+        let passes = effects
+            .iter()
+            .flat_map(|effect| (0..effect.get_passes().len()).map(move |index| (*effect, index)));
+
+        // depth test and blending would discard or mix the fullscreen triangle
+        let capabilities = get_capabilities();
+        set_capabilities(Capabilities::default());
+        let framebuffer = get_framebuffer();
+        unsafe {
+            gl::BindVertexArray(self.vertex_array);
+        }
+        for ((effect, index), plan) in passes.zip(plan_passes(&pass_counts)) {
+            match plan.target {
+                Some(target) => self.targets[target].bind(),
+                None => bind_output(output),
+            }
+            effect.prepare_pass(
+                index,
+                self.get_input(plan.source, input),
+                self.get_input(plan.original, input),
+            );
+            unsafe {
+                gl::DrawArrays(gl::TRIANGLES, 0, 3);
+            }
+            match plan.target {
+                Some(target) => self.targets[target].unbind(),
+                None => unbind_output(output, framebuffer),
+            }
+        }
+        unsafe {
+            gl::BindVertexArray(0);
+        }
+        set_capabilities(capabilities);
+    }
+
+    fn get_input<'a>(&'a self, target: Option<usize>, input: &'a Texture) -> &'a Texture {
+        match target {
+            Some(index) => &self.targets[index].get_textures()[0],
+            None => input,
+        }
+    }
+}
+
+impl Drop for PostProcess {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+        }
+    }
+}
+
+fn build_scene_target(width: u32, height: u32, samples: u32) -> Result<RenderTarget, Error> {
+    let mut builder = RenderTargetBuilder::new(width, height);
+    builder.set_samples(samples);
+    builder.build()
+}
+
+fn build_targets(width: u32, height: u32) -> Result<[RenderTarget; 3], Error> {
+    let mut builder = RenderTargetBuilder::new(width, height);
+    builder.set_depth(false);
+    builder.set_stencil(false);
+    Ok([builder.build()?, builder.build()?, builder.build()?])
+}
+
+fn bind_output(output: Option<&RenderTarget>) {
+    match output {
+        Some(target) => target.bind(),
+        None => bind_framebuffer(0),
+    }
+}
+
+/// `previous` is the framebuffer bound when `PostProcess::apply` started
+fn unbind_output(output: Option<&RenderTarget>, previous: u32) {
+    match output {
+        Some(target) => target.unbind(),
+        None => bind_framebuffer(previous),
+    }
+}
+
+/// Plans the passes of effects with `pass_counts` passes each. A pass never
+/// writes to the target it reads or to the one holding the input of its
+/// effect, and the last pass writes to the output.
+fn plan_passes(pass_counts: &[usize]) -> Vec<PassPlan> {
+    let total: usize = pass_counts.iter().sum();
+    let mut result = Vec::with_capacity(total);
+    let mut source = None;
+    for count in pass_counts {
+        let original = source;
+        for _ in 0..*count {
+            let target = if result.len() + 1 == total {
+                None
+            } else {
+                (0..3).find(|index| Some(*index) != source && Some(*index) != original)
+            };
+            result.push(PassPlan {
+                source,
+                original,
+                target,
+            });
+            source = target;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(source: Option<usize>, original: Option<usize>, target: Option<usize>) -> PassPlan {
+        PassPlan {
+            source,
+            original,
+            target,
+        }
+    }
+
+    #[test]
+    fn test_plan_single_pass_writes_output() {
+        assert_eq!(vec![plan(None, None, None)], plan_passes(&[1]));
+    }
+
+    #[test]
+    fn test_plan_ping_pong() {
+        assert_eq!(
+            vec![
+                plan(None, None, Some(0)),
+                plan(Some(0), Some(0), Some(1)),
+                plan(Some(1), Some(1), None),
+            ],
+            plan_passes(&[1, 1, 1])
+        );
+    }
+
+    #[test]
+    fn test_plan_keeps_effect_input() {
+        let result = plan_passes(&[1, 4]);
+        assert_eq!(5, result.len());
+        for pass in &result[1..] {
+            assert_eq!(Some(0), pass.original);
+            assert_ne!(Some(0), pass.target);
+        }
+        assert_eq!(None, result[4].target);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::error::Error;
+use crate::math::vector::Vec2;
+use crate::polygons::texture::units::bind_samplers;
+use crate::polygons::texture::{Filtering, Texture, Wrapping};
+use crate::shaders::shader_program::ShaderProgram;
+use crate::shaders::uniform::Uniform;
+use crate::shaders::utils::create_shader_program;
+use image::RgbaImage;
+
+/// Fragment shader drawn on a fullscreen triangle, reads the previous result
+/// from `uniform sampler2D screenTexture` at `in vec2 TexCoord`
+pub struct Pass {
+    shader: ShaderProgram,
+    uniforms: Vec<(String, Box<dyn Uniform>)>,
+}
+
+/// Named group of passes with the uniforms and textures shared by them
+pub struct Effect {
+    name: String,
+    passes: Vec<Pass>,
+    uniforms: Vec<(String, Box<dyn Uniform>)>,
+    textures: Vec<(String, Texture)>,
+    is_enabled: bool,
+}
+
+impl Pass {
+    /// `fragment_name` is a fragment shader of the shader registry
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a shader source could not be read or compiled
+    pub fn new(fragment_name: &str) -> Result<Self, Error> {
+        Pass::from_program(create_shader_program("fullscreen.vert", fragment_name)?)
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the fullscreen vertex shader could not be read or
+    /// the program could not be compiled
+    pub fn from_fragment_source(fragment_src: &str) -> Result<Self, Error> {
+        let mut shader = create_shader_program("fullscreen.vert", "post_copy.frag")?;
+        shader.set_fragment_shader(fragment_src);
+        Pass::from_program(shader)
+    }
+
+    /// `shader` should use the `fullscreen.vert` vertex shader or one that
+    /// does not read any vertex attributes
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the program could not be compiled
+    pub fn from_program(mut shader: ShaderProgram) -> Result<Self, Error> {
+        shader.compile()?;
+        Ok(Pass {
+            shader,
+            uniforms: vec![],
+        })
+    }
+
+    /// Uniform used only by this pass, takes precedence over the uniforms of
+    /// the effect
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the shader of the pass has no active uniform `name`
+    pub fn set_uniform<T: Uniform + 'static>(&mut self, name: &str, value: T) -> Result<(), Error> {
+        self.shader.require_uniforms(&[name])?;
+        set_value(&mut self.uniforms, name, Box::new(value));
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.shader.get_uniform_location(name).is_some()
+    }
+
+    #[must_use]
+    pub fn get_shader(&self) -> &ShaderProgram {
+        &self.shader
+    }
+
+    pub fn get_shader_mut(&mut self) -> &mut ShaderProgram {
+        &mut self.shader
+    }
+
+    /// Binds the inputs and uploads the uniforms, the caller draws the
+    /// fullscreen triangle
+    fn prepare(&self, effect: &Effect, source: &Texture, original: &Texture) {
+        self.shader.activate();
+        // `originalTexture` is the input of the effect, for passes that
+        // combine with it
+        let inputs = [("screenTexture", source), ("originalTexture", original)];
+        let extra = effect
+            .textures
+            .iter()
+            .map(|(name, texture)| (name.as_str(), texture));
+        // passes sample only some of the inputs and effect textures
+        let used = inputs
+            .into_iter()
+            .chain(extra)
+            .filter(|(name, _)| self.has_uniform(name));
+        bind_samplers(&self.shader, used);
+
+        if self.has_uniform("texelSize") {
+            let (width, height) = source.get_size();
+            let texel_size = Vec2::new(1.0 / width.max(1) as f32, 1.0 / height.max(1) as f32);
+            self.set_checked_uniform("texelSize", &texel_size);
+        }
+
+        // an effect shares its values between passes that use only some of
+        // them, `Effect::set_uniform` checked that at least one pass does
+        for (name, value) in effect.uniforms.iter().chain(&self.uniforms) {
+            if self.has_uniform(name) {
+                self.set_checked_uniform(name, value.as_ref());
+            }
+        }
+    }
+
+    fn set_checked_uniform<T: Uniform + ?Sized>(&self, name: &str, value: &T) {
+        if let Err(error) = self.shader.set_uniform(name, value) {
+            panic!("post-processing pass: {error}");
+        }
+    }
+}
+
+impl Effect {
+    #[must_use]
+    pub fn new(name: &str, passes: Vec<Pass>) -> Self {
+        Effect {
+            name: String::from(name),
+            passes,
+            uniforms: vec![],
+            textures: vec![],
+            is_enabled: true,
+        }
+    }
+
+    /// `intensity` in [0, 1] blends between the original and gray colors
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the built-in shaders could not be loaded
+    pub fn grayscale(intensity: f32) -> Result<Self, Error> {
+        let mut result = Effect::new("grayscale", vec![Pass::new("post_grayscale.frag")?]);
+        result.set_uniform("intensity", intensity)?;
+        Ok(result)
+    }
+
+    /// Separable gaussian blur, `radius` scales the distance between samples
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the built-in shaders could not be loaded
+    pub fn blur(radius: f32) -> Result<Self, Error> {
+        let mut result = Effect::new("blur", blur_passes()?.into());
+        result.set_uniform("radius", radius)?;
+        Ok(result)
+    }
+
+    /// Blurs the areas brighter than `threshold` and adds them to the image
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the built-in shaders could not be loaded
+    pub fn bloom(threshold: f32, intensity: f32) -> Result<Self, Error> {
+        let [horizontal, vertical] = blur_passes()?;
+        let passes = vec![
+            Pass::new("post_bloom_extract.frag")?,
+            horizontal,
+            vertical,
+            Pass::new("post_bloom_combine.frag")?,
+        ];
+        let mut result = Effect::new("bloom", passes);
+        result.set_uniform("threshold", threshold)?;
+        result.set_uniform("intensity", intensity)?;
+        result.set_uniform("radius", 1.0_f32)?;
+        Ok(result)
+    }
+
+    /// Darkens the image from `radius` to `radius + softness`, measured from
+    /// the center in texture coordinates
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the built-in shaders could not be loaded
+    pub fn vignette(radius: f32, softness: f32) -> Result<Self, Error> {
+        let mut result = Effect::new("vignette", vec![Pass::new("post_vignette.frag")?]);
+        result.set_uniform("radius", radius)?;
+        result.set_uniform("softness", softness)?;
+        result.set_uniform("strength", 1.0_f32)?;
+        Ok(result)
+    }
+
+    /// Maps colors through `lut`, a strip of `size` slices of `size * size`
+    /// pixels laid out like `neutral_lut`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the built-in shaders could not be loaded
+    pub fn color_grading(mut lut: Texture, size: u32) -> Result<Self, Error> {
+        lut.upload();
+        lut.set_filtering(Filtering::Linear);
+        lut.set_wrapping(Wrapping::ClampToEdge);
+
+        let mut result = Effect::new("color_grading", vec![Pass::new("post_color_grading.frag")?]);
+        result.set_texture("lutTexture", lut)?;
+        result.set_uniform("lutSize", size as f32)?;
+        result.set_uniform("intensity", 1.0_f32)?;
+        Ok(result)
+    }
+
+    /// Fast approximate anti-aliasing, best applied as the last effect
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the built-in shaders could not be loaded
+    pub fn fxaa() -> Result<Self, Error> {
+        Ok(Effect::new("fxaa", vec![Pass::new("post_fxaa.frag")?]))
+    }
+
+    /// Uploaded to every pass of the effect that has an active uniform `name`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if none of the passes has an active uniform `name`
+    pub fn set_uniform<T: Uniform + 'static>(&mut self, name: &str, value: T) -> Result<(), Error> {
+        self.require_uniform(name)?;
+        set_value(&mut self.uniforms, name, Box::new(value));
+        Ok(())
+    }
+
+    /// Extra texture bound to the `sampler2D` uniform `name`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if none of the passes has a sampler `name`
+    pub fn set_texture(&mut self, name: &str, texture: Texture) -> Result<(), Error> {
+        self.require_uniform(name)?;
+        set_value(&mut self.textures, name, texture);
+        Ok(())
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn get_passes(&self) -> &[Pass] {
+        &self.passes
+    }
+
+    pub fn get_passes_mut(&mut self) -> &mut [Pass] {
+        &mut self.passes
+    }
+
+    fn require_uniform(&self, name: &str) -> Result<(), Error> {
+        if self.passes.iter().any(|pass| pass.has_uniform(name)) {
+            Ok(())
+        } else {
+            Err(Error::UniformNotFound(String::from(name)))
+        }
+    }
+
+    pub(crate) fn prepare_pass(&self, index: usize, source: &Texture, original: &Texture) {
+        self.passes[index].prepare(self, source, original);
+    }
+}
+
+/// Identity lookup table for `Effect::color_grading`, edit it in an image
+/// editor together with a screenshot to make a custom grading
+#[must_use]
+pub fn neutral_lut(size: u32) -> RgbaImage {
+    let size = size.max(2);
+    let max = (size - 1) as f32;
+    RgbaImage::from_fn(size * size, size, |x, y| {
+        let to_byte = |value: u32| (value as f32 / max * 255.0).round() as u8;
+        image::Rgba([to_byte(x % size), to_byte(y), to_byte(x / size), 255])
+    })
+}
+
+fn blur_passes() -> Result<[Pass; 2], Error> {
+    let mut horizontal = Pass::new("post_blur.frag")?;
+    horizontal.set_uniform("direction", Vec2::new(1.0, 0.0))?;
+    let mut vertical = Pass::new("post_blur.frag")?;
+    vertical.set_uniform("direction", Vec2::new(0.0, 1.0))?;
+    Ok([horizontal, vertical])
+}
+
+/// Replaces the value of `name` or appends it, keeping the insertion order
+fn set_value<T>(values: &mut Vec<(String, T)>, name: &str, value: T) {
+    match values.iter_mut().find(|(key, _)| key == name) {
+        Some((_, current)) => *current = value,
+        None => values.push((String::from(name), value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neutral_lut_layout() {
+        let lut = neutral_lut(4);
+        assert_eq!((16, 4), lut.dimensions());
+        assert_eq!(&image::Rgba([0, 0, 0, 255]), lut.get_pixel(0, 0));
+        assert_eq!(&image::Rgba([255, 85, 0, 255]), lut.get_pixel(3, 1));
+        assert_eq!(&image::Rgba([0, 255, 255, 255]), lut.get_pixel(12, 3));
+    }
+
+    #[test]
+    fn test_set_value_replaces() {
+        let mut values = vec![];
+        set_value(&mut values, "radius", 1);
+        set_value(&mut values, "softness", 2);
+        set_value(&mut values, "radius", 3);
+        assert_eq!(
+            vec![(String::from("radius"), 3), (String::from("softness"), 2)],
+            values
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use image::RgbaImage;
+
+/// Color buffer of the default framebuffer to read from
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorBuffer {
+    /// What is currently on the screen
+    Front = 0x0404,
+    /// What is being drawn and will be shown by the next `swap_buffers`
+    Back = 0x0405,
+}
+
+/// Reads the color attachment of `framebuffer` (0 for the default one) from
+/// `read_buffer`. Rows are tightly packed RGBA and the result is top row
+/// first. The previous read framebuffer, read buffer and pack alignment are
+/// restored.
+pub(crate) fn read_pixels(
+    framebuffer: u32,
+    read_buffer: u32,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let mut buffer = vec![0_u8; width as usize * height as usize * 4];
+    unsafe {
+        let mut previous_framebuffer = 0;
+        let mut previous_read_buffer = 0;
+        let mut previous_alignment = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+        gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut previous_alignment);
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer);
+        gl::GetIntegerv(gl::READ_BUFFER, &mut previous_read_buffer);
+        gl::ReadBuffer(read_buffer);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0,
+            0,
+            i32::try_from(width).unwrap_or(i32::MAX),
+            i32::try_from(height).unwrap_or(i32::MAX),
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            buffer.as_mut_ptr().cast::<std::ffi::c_void>(),
+        );
+
+        gl::PixelStorei(gl::PACK_ALIGNMENT, previous_alignment);
+        gl::ReadBuffer(u32::try_from(previous_read_buffer).unwrap_or(read_buffer));
+        gl::BindFramebuffer(
+            gl::READ_FRAMEBUFFER,
+            u32::try_from(previous_framebuffer).unwrap_or(0),
+        );
+    }
+    image_from_gl_rows(width, height, buffer)
+}
+
+/// OpenGL returns the bottom row first
+fn image_from_gl_rows(width: u32, height: u32, buffer: Vec<u8>) -> RgbaImage {
+    let mut result = RgbaImage::from_raw(width, height, buffer)
+        .expect("buffer holds exactly width * height RGBA pixels");
+    image::imageops::flip_vertical_in_place(&mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_from_gl_rows_flips() {
+        let bottom = [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255];
+        let top = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let result = image_from_gl_rows(3, 2, [bottom, top].concat());
+
+        assert_eq!(&image::Rgba([1, 2, 3, 4]), result.get_pixel(0, 0));
+        assert_eq!(&image::Rgba([0, 0, 255, 255]), result.get_pixel(2, 1));
+    }
+}
@@ -0,0 +1,368 @@
+use crate::error::Error;
+use crate::gl_state;
+use crate::polygons::texture::Texture;
+use crate::readback::read_pixels;
+use image::RgbaImage;
+use std::cell::Cell;
+
+/// Offscreen framebuffer whose color attachments are textures that can be
+/// passed to `PolygonBuilder::set_texture`.
+///
+/// With multisampling the scene is drawn into multisampled renderbuffers and
+/// resolved into the textures by `unbind` or `resolve`.
+pub struct RenderTarget {
+    width: u32,
+    height: u32,
+    samples: u32,
+    framebuffer: u32,
+    multisample_framebuffer: Option<u32>,
+    renderbuffers: Vec<u32>,
+    textures: Vec<Texture>,
+    /// Framebuffer and viewport bound before `bind`
+    previous: Cell<Option<(u32, [i32; 4])>>,
+}
+
+pub struct RenderTargetBuilder {
+    width: u32,
+    height: u32,
+    color_attachments: u32,
+    has_depth: bool,
+    has_stencil: bool,
+    samples: u32,
+}
+
+impl RenderTarget {
+    /// Draws into the target instead of the window or the previously bound
+    /// target until `unbind`, the viewport is set to the size of the target
+    pub fn bind(&self) {
+        self.previous.set(Some((
+            gl_state::get_framebuffer(),
+            gl_state::get_viewport(),
+        )));
+        gl_state::bind_framebuffer(self.get_draw_framebuffer());
+        gl_state::set_viewport([0, 0, to_i32(self.width), to_i32(self.height)]);
+    }
+
+    /// Goes back to the framebuffer and viewport bound before `bind`,
+    /// resolving multisampled contents into the textures
+    pub fn unbind(&self) {
+        self.resolve();
+        let (framebuffer, viewport) = self
+            .previous
+            .take()
+            .unwrap_or((0, gl_state::get_viewport()));
+        gl_state::bind_framebuffer(framebuffer);
+        gl_state::set_viewport(viewport);
+    }
+
+    /// Copies the multisampled attachments into the textures, does nothing
+    /// without multisampling
+    pub fn resolve(&self) {
+        let Some(multisample_framebuffer) = self.multisample_framebuffer else {
+            return;
+        };
+
+        let (width, height) = (to_i32(self.width), to_i32(self.height));
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, multisample_framebuffer);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.framebuffer);
+            for index in 0..self.textures.len() {
+                let attachment = gl::COLOR_ATTACHMENT0 + u32::try_from(index).unwrap();
+                gl::ReadBuffer(attachment);
+                gl::DrawBuffers(1, &attachment);
+                gl::BlitFramebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    gl::COLOR_BUFFER_BIT,
+                    gl::NEAREST,
+                );
+            }
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            set_draw_buffers(self.textures.len());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, gl_state::get_framebuffer());
+        }
+    }
+
+    /// First color attachment, shares the OpenGL texture with the target
+    #[must_use]
+    pub fn get_texture(&self) -> Texture {
+        self.textures[0].clone()
+    }
+
+    #[must_use]
+    pub fn get_textures(&self) -> &[Texture] {
+        &self.textures
+    }
+
+    #[must_use]
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    #[must_use]
+    pub fn get_samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Reads a resolved color attachment, top row first
+    #[must_use]
+    pub fn read_pixels(&self, attachment: usize) -> Option<RgbaImage> {
+        if attachment >= self.textures.len() {
+            return None;
+        }
+        let read_buffer = gl::COLOR_ATTACHMENT0 + u32::try_from(attachment).ok()?;
+        Some(read_pixels(
+            self.framebuffer,
+            read_buffer,
+            self.width,
+            self.height,
+        ))
+    }
+
+    fn get_draw_framebuffer(&self) -> u32 {
+        self.multisample_framebuffer.unwrap_or(self.framebuffer)
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            if let Some(multisample_framebuffer) = self.multisample_framebuffer {
+                gl::DeleteFramebuffers(1, &multisample_framebuffer);
+            }
+            for renderbuffer in &self.renderbuffers {
+                gl::DeleteRenderbuffers(1, renderbuffer);
+            }
+        }
+    }
+}
+
+impl RenderTargetBuilder {
+    /// One color texture with a depth and stencil renderbuffer, no multisampling
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        RenderTargetBuilder {
+            width,
+            height,
+            color_attachments: 1,
+            has_depth: true,
+            has_stencil: true,
+            samples: 0,
+        }
+    }
+
+    /// Number of color textures, written by `layout (location = N) out` in
+    /// fragment shaders
+    pub fn set_color_attachments(&mut self, count: u32) {
+        self.color_attachments = count.max(1);
+    }
+
+    pub fn set_depth(&mut self, has_depth: bool) {
+        self.has_depth = has_depth;
+    }
+
+    pub fn set_stencil(&mut self, has_stencil: bool) {
+        self.has_stencil = has_stencil;
+    }
+
+    /// Number of MSAA samples, 0 disables multisampling. Clamped to what the
+    /// driver supports.
+    pub fn set_samples(&mut self, samples: u32) {
+        self.samples = samples;
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the size is zero or a framebuffer is incomplete
+    pub fn build(&self) -> Result<RenderTarget, Error> {
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::IncompleteFramebuffer(format!(
+                "invalid size {}x{}",
+                self.width, self.height
+            )));
+        }
+
+        let mut max_samples = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_SAMPLES, &mut max_samples);
+        }
+        let samples = self.samples.min(u32::try_from(max_samples).unwrap_or(0));
+
+        let mut result = RenderTarget {
+            width: self.width,
+            height: self.height,
+            samples,
+            framebuffer: generate_framebuffer(),
+            multisample_framebuffer: None,
+            renderbuffers: vec![],
+            textures: vec![],
+            previous: Cell::new(None),
+        };
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, result.framebuffer);
+        }
+        for index in 0..self.color_attachments {
+            let texture = Texture::empty(self.width, self.height);
+            unsafe {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + index,
+                    gl::TEXTURE_2D,
+                    texture.get_id(),
+                    0,
+                );
+            }
+            result.textures.push(texture);
+        }
+        set_draw_buffers(result.textures.len());
+
+        if samples > 0 {
+            let multisample_framebuffer = generate_framebuffer();
+            result.multisample_framebuffer = Some(multisample_framebuffer);
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, multisample_framebuffer);
+            }
+            for index in 0..self.color_attachments {
+                let renderbuffer =
+                    self.attach_renderbuffer(gl::RGBA8, gl::COLOR_ATTACHMENT0 + index, samples);
+                result.renderbuffers.push(renderbuffer);
+            }
+            set_draw_buffers(result.textures.len());
+        }
+
+        if let Some((format, attachment)) = self.get_depth_stencil_format() {
+            let renderbuffer = self.attach_renderbuffer(format, attachment, samples);
+            result.renderbuffers.push(renderbuffer);
+        }
+
+        let status = check_framebuffer_status(result.get_draw_framebuffer());
+        let resolve_status = check_framebuffer_status(result.framebuffer);
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, gl_state::get_framebuffer());
+        }
+        status.and(resolve_status)?;
+        Ok(result)
+    }
+
+    fn get_depth_stencil_format(&self) -> Option<(u32, u32)> {
+        match (self.has_depth, self.has_stencil) {
+            (true, true) => Some((gl::DEPTH24_STENCIL8, gl::DEPTH_STENCIL_ATTACHMENT)),
+            (true, false) => Some((gl::DEPTH_COMPONENT24, gl::DEPTH_ATTACHMENT)),
+            (false, true) => Some((gl::STENCIL_INDEX8, gl::STENCIL_ATTACHMENT)),
+            (false, false) => None,
+        }
+    }
+
+    /// Attaches a new renderbuffer to the bound framebuffer
+    fn attach_renderbuffer(&self, format: u32, attachment: u32, samples: u32) -> u32 {
+        let mut renderbuffer = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut renderbuffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+            gl::RenderbufferStorageMultisample(
+                gl::RENDERBUFFER,
+                to_i32(samples),
+                format,
+                to_i32(self.width),
+                to_i32(self.height),
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                attachment,
+                gl::RENDERBUFFER,
+                renderbuffer,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+        renderbuffer
+    }
+}
+
+fn generate_framebuffer() -> u32 {
+    let mut result = 0;
+    unsafe {
+        gl::GenFramebuffers(1, &mut result);
+    }
+    result
+}
+
+/// Enables the first `count` color attachments of the bound framebuffer
+fn set_draw_buffers(count: usize) {
+    let attachments: Vec<u32> = (0..u32::try_from(count).unwrap())
+        .map(|index| gl::COLOR_ATTACHMENT0 + index)
+        .collect();
+    unsafe {
+        gl::DrawBuffers(to_i32(attachments.len()), attachments.as_ptr());
+    }
+}
+
+fn check_framebuffer_status(framebuffer: u32) -> Result<(), Error> {
+    let status = unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl::CheckFramebufferStatus(gl::FRAMEBUFFER)
+    };
+    match framebuffer_status_name(status) {
+        None => Ok(()),
+        Some(name) => Err(Error::IncompleteFramebuffer(String::from(name))),
+    }
+}
+
+/// `None` for a complete framebuffer
+fn framebuffer_status_name(status: u32) -> Option<&'static str> {
+    match status {
+        gl::FRAMEBUFFER_COMPLETE => None,
+        gl::FRAMEBUFFER_UNDEFINED => Some("GL_FRAMEBUFFER_UNDEFINED"),
+        gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => Some("GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT"),
+        gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+            Some("GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT")
+        }
+        gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => Some("GL_FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER"),
+        gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => Some("GL_FRAMEBUFFER_INCOMPLETE_READ_BUFFER"),
+        gl::FRAMEBUFFER_UNSUPPORTED => Some("GL_FRAMEBUFFER_UNSUPPORTED"),
+        gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => Some("GL_FRAMEBUFFER_INCOMPLETE_MULTISAMPLE"),
+        gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => Some("GL_FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS"),
+        _ => Some("unknown framebuffer status"),
+    }
+}
+
+fn to_i32(value: impl TryInto<i32>) -> i32 {
+    value.try_into().unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framebuffer_status_name() {
+        assert_eq!(None, framebuffer_status_name(gl::FRAMEBUFFER_COMPLETE));
+        assert_eq!(
+            Some("GL_FRAMEBUFFER_INCOMPLETE_MULTISAMPLE"),
+            framebuffer_status_name(gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE)
+        );
+    }
+
+    #[test]
+    fn test_render_target_depth_stencil_format() {
+        let mut builder = RenderTargetBuilder::new(64, 64);
+        assert_eq!(
+            Some((gl::DEPTH24_STENCIL8, gl::DEPTH_STENCIL_ATTACHMENT)),
+            builder.get_depth_stencil_format()
+        );
+        builder.set_stencil(false);
+        assert_eq!(
+            Some((gl::DEPTH_COMPONENT24, gl::DEPTH_ATTACHMENT)),
+            builder.get_depth_stencil_format()
+        );
+        builder.set_depth(false);
+        assert_eq!(None, builder.get_depth_stencil_format());
+    }
+}
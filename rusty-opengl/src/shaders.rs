@@ -1,2 +1,10 @@
+pub mod compute;
+pub mod diagnostic;
+pub mod hot_reload;
+pub mod preprocessor;
+pub mod reflection;
+pub mod registry;
 pub mod shader_program;
+pub mod uniform;
 pub mod utils;
+pub mod variants;
@@ -0,0 +1,206 @@
+use crate::error::Error;
+use crate::shaders::reflection::ProgramReflection;
+use crate::shaders::shader_program::{get_context_version, LinkedProgram, ShaderType};
+use crate::shaders::uniform::Uniform;
+use crate::shaders::utils::read_src_from_file;
+use std::ops::BitOr;
+use std::path::Path;
+
+/// Program with a single compute stage, requires OpenGL 4.3
+pub struct ComputeProgram {
+    program: LinkedProgram,
+    src: String,
+    is_compiled: bool,
+    work_group_size: [u32; 3],
+}
+
+/// Flags for `memory_barrier`, combined with `|`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MemoryBarriers(u32);
+
+impl MemoryBarriers {
+    pub const VERTEX_ATTRIB_ARRAY: Self = Self(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    pub const ELEMENT_ARRAY: Self = Self(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: Self = Self(gl::UNIFORM_BARRIER_BIT);
+    pub const TEXTURE_FETCH: Self = Self(gl::TEXTURE_FETCH_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: Self = Self(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const COMMAND: Self = Self(gl::COMMAND_BARRIER_BIT);
+    pub const PIXEL_BUFFER: Self = Self(gl::PIXEL_BUFFER_BARRIER_BIT);
+    pub const TEXTURE_UPDATE: Self = Self(gl::TEXTURE_UPDATE_BARRIER_BIT);
+    pub const BUFFER_UPDATE: Self = Self(gl::BUFFER_UPDATE_BARRIER_BIT);
+    pub const FRAMEBUFFER: Self = Self(gl::FRAMEBUFFER_BARRIER_BIT);
+    pub const SHADER_STORAGE: Self = Self(gl::SHADER_STORAGE_BARRIER_BIT);
+    pub const ALL: Self = Self(gl::ALL_BARRIER_BITS);
+
+    #[must_use]
+    pub fn get_bits(self) -> u32 {
+        self.0
+    }
+
+    #[must_use]
+    pub fn contains(self, other: MemoryBarriers) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for MemoryBarriers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ComputeProgram {
+    #[must_use]
+    pub fn new(src: &str) -> Self {
+        ComputeProgram {
+            program: LinkedProgram::new(),
+            src: String::from(src),
+            is_compiled: false,
+            work_group_size: [1, 1, 1],
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the file could not be read
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        Ok(ComputeProgram::new(&read_src_from_file(path)?))
+    }
+
+    pub fn set_source(&mut self, src: &str) {
+        self.src = String::from(src);
+        self.is_compiled = false;
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Error::UnsupportedFeature` if the context is older than
+    /// 4.3, otherwise the diagnostic of the failed compile or link step
+    pub fn compile(&mut self) -> Result<(), Error> {
+        if self.is_compiled {
+            return Ok(());
+        }
+        ShaderType::Compute.check_support(get_context_version())?;
+
+        self.program.link(&[(ShaderType::Compute, &self.src)])?;
+
+        let mut size = [1; 3];
+        unsafe {
+            gl::GetProgramiv(
+                self.program.get_id(),
+                gl::COMPUTE_WORK_GROUP_SIZE,
+                size.as_mut_ptr(),
+            );
+        }
+        self.work_group_size = size.map(|value| u32::try_from(value).unwrap_or(1));
+
+        self.is_compiled = true;
+        Ok(())
+    }
+
+    pub fn activate(&self) {
+        self.program.activate();
+    }
+
+    #[must_use]
+    pub fn is_compiled(&self) -> bool {
+        self.is_compiled
+    }
+
+    /// `local_size_x`, `local_size_y` and `local_size_z` of the linked shader
+    #[must_use]
+    pub fn get_work_group_size(&self) -> [u32; 3] {
+        self.work_group_size
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the program is not linked or has no active uniform
+    /// with this name
+    pub fn set_uniform<T: Uniform + ?Sized>(&self, name: &str, value: &T) -> Result<(), Error> {
+        self.program.set_uniform(name, value)
+    }
+
+    /// Active uniforms after the last successful link
+    #[must_use]
+    pub fn get_reflection(&self) -> &ProgramReflection {
+        self.program.get_reflection()
+    }
+
+    #[must_use]
+    pub fn get_uniform_location(&self, name: &str) -> Option<i32> {
+        self.program.get_uniform_location(name)
+    }
+
+    /// Activates the program and runs `groups` work groups
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the program is not compiled
+    pub fn dispatch(&self, groups: [u32; 3]) -> Result<(), Error> {
+        if !self.is_compiled {
+            return Err(Error::InvalidProgram(String::from(
+                "dispatch needs a compiled compute program",
+            )));
+        }
+        self.activate();
+        unsafe {
+            gl::DispatchCompute(groups[0], groups[1], groups[2]);
+        }
+        Ok(())
+    }
+
+    /// Runs at least one invocation per element of `size`, e.g. per pixel of
+    /// an image. Shaders should skip invocations outside of `size`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the program is not compiled
+    pub fn dispatch_for(&self, size: [u32; 3]) -> Result<(), Error> {
+        self.dispatch(get_group_count(size, self.work_group_size))
+    }
+}
+
+/// Makes writes of shaders visible to the operations in `barriers`
+///
+/// # Errors
+///
+/// Will return `Err` if the context is older than 4.2
+pub fn memory_barrier(barriers: MemoryBarriers) -> Result<(), Error> {
+    if !gl::MemoryBarrier::is_loaded() {
+        return Err(Error::UnsupportedFeature(String::from(
+            "memory barriers need OpenGL 4.2",
+        )));
+    }
+    unsafe {
+        gl::MemoryBarrier(barriers.get_bits());
+    }
+    Ok(())
+}
+
+/// Work groups needed to cover `size` with groups of `work_group_size`
+#[must_use]
+pub fn get_group_count(size: [u32; 3], work_group_size: [u32; 3]) -> [u32; 3] {
+    [0, 1, 2].map(|axis| size[axis].div_ceil(work_group_size[axis].max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_count() {
+        assert_eq!([8, 5, 1], get_group_count([64, 33, 1], [8, 8, 1]));
+        assert_eq!([0, 1, 1], get_group_count([0, 1, 1], [16, 0, 1]));
+    }
+
+    #[test]
+    fn test_memory_barriers() {
+        let barriers = MemoryBarriers::SHADER_STORAGE | MemoryBarriers::TEXTURE_FETCH;
+        assert!(barriers.contains(MemoryBarriers::TEXTURE_FETCH));
+        assert!(!barriers.contains(MemoryBarriers::UNIFORM));
+        assert!(MemoryBarriers::ALL.contains(barriers));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::shaders::shader_program::ShaderType;
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Single message extracted from a driver info log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticMessage {
+    pub severity: Severity,
+    /// Source string number, see `PreprocessedSource::files`
+    pub source_string: Option<u32>,
+    /// 1-based line in the shader source, if the driver reported one
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub text: String,
+    /// Source line the message points to
+    pub source_line: Option<String>,
+}
+
+/// Outcome of a failed compile or link step
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    /// Stage that failed to compile, `None` when linking failed
+    pub stage: Option<ShaderType>,
+    /// Full, unmodified driver info log
+    pub log: String,
+    pub messages: Vec<DiagnosticMessage>,
+}
+
+impl ShaderDiagnostic {
+    #[must_use]
+    pub fn compilation(stage: ShaderType, log: &str, source: &str) -> Self {
+        ShaderDiagnostic {
+            stage: Some(stage),
+            log: String::from(log),
+            messages: parse_info_log(log, Some(source)),
+        }
+    }
+
+    #[must_use]
+    pub fn link(log: &str) -> Self {
+        ShaderDiagnostic {
+            stage: None,
+            log: String::from(log),
+            messages: parse_info_log(log, None),
+        }
+    }
+
+    #[must_use]
+    pub fn errors(&self) -> Vec<&DiagnosticMessage> {
+        self.messages
+            .iter()
+            .filter(|message| message.severity == Severity::Error)
+            .collect()
+    }
+}
+
+impl fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.stage {
+            Some(stage) => writeln!(f, "{stage} shader compilation error")?,
+            None => writeln!(f, "Shader program link error")?,
+        }
+        write!(f, "{}", self.log.trim_end())
+    }
+}
+
+/// Splits a driver info log into messages.
+///
+/// Understands the common vendor formats:
+/// `0:12(5): error: ...` (Mesa), `0(12) : error C0000: ...` (NVIDIA) and
+/// `ERROR: 0:12: ...` (AMD, Intel). Lines in an unknown format are kept as
+/// messages without a location.
+#[must_use]
+pub fn parse_info_log(log: &str, source: Option<&str>) -> Vec<DiagnosticMessage> {
+    log.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut message = parse_line(line);
+            if let (Some(source), Some(line)) = (source, message.line) {
+                message.source_line =
+                    find_source_line(source, message.source_string.unwrap_or(0), line);
+            }
+            message
+        })
+        .collect()
+}
+
+fn parse_line(line: &str) -> DiagnosticMessage {
+    if let Some(message) = parse_prefixed_severity(line) {
+        return message;
+    }
+    if let Some(message) = parse_location_first(line) {
+        return message;
+    }
+
+    DiagnosticMessage {
+        severity: severity_from(line),
+        source_string: None,
+        line: None,
+        column: None,
+        text: String::from(line),
+        source_line: None,
+    }
+}
+
+/// `ERROR: 0:12: 'foo' : undeclared identifier`
+fn parse_prefixed_severity(line: &str) -> Option<DiagnosticMessage> {
+    let (severity, rest) = line.split_once(':')?;
+    let severity = match severity.trim().to_ascii_uppercase().as_str() {
+        "ERROR" => Severity::Error,
+        "WARNING" => Severity::Warning,
+        _ => return None,
+    };
+    let mut parts = rest.splitn(3, ':');
+    let source_string = parts.next()?.trim().parse::<u32>().ok()?;
+    let line_number = parts.next()?.trim().parse::<u32>().ok()?;
+    Some(DiagnosticMessage {
+        severity,
+        source_string: Some(source_string),
+        line: Some(line_number),
+        column: None,
+        text: String::from(parts.next().unwrap_or("").trim()),
+        source_line: None,
+    })
+}
+
+/// `0:12(5): error: ...` or `0(12) : error C0000: ...`
+fn parse_location_first(line: &str) -> Option<DiagnosticMessage> {
+    let (location, rest) = split_location(line)?;
+    let source_string = location
+        .split([':', '('])
+        .next()
+        .and_then(|number| number.trim().parse::<u32>().ok());
+    let (line_number, column) = if let Some((_, position)) = location.split_once(':') {
+        match position.split_once('(') {
+            Some((line_number, column)) => (
+                line_number.trim().parse::<u32>().ok()?,
+                column.trim_end_matches(')').trim().parse::<u32>().ok(),
+            ),
+            None => (position.trim().parse::<u32>().ok()?, None),
+        }
+    } else {
+        let (_, line_number) = location.split_once('(')?;
+        (
+            line_number
+                .trim_end_matches(')')
+                .trim()
+                .parse::<u32>()
+                .ok()?,
+            None,
+        )
+    };
+
+    let rest = rest.trim();
+    let (severity, text) = match rest.split_once(':') {
+        Some((kind, text)) if is_severity_word(kind) => (severity_from(kind), text.trim()),
+        _ => (severity_from(rest), rest),
+    };
+    Some(DiagnosticMessage {
+        severity,
+        source_string,
+        line: Some(line_number),
+        column,
+        text: String::from(text),
+        source_line: None,
+    })
+}
+
+/// Line `line` of source string `source_string`, following the `#line`
+/// directives written by the preprocessor
+fn find_source_line(source: &str, source_string: u32, line: u32) -> Option<String> {
+    let mut current_string = 0;
+    let mut current_line = 1;
+    for text in source.lines() {
+        if let Some(arguments) = text.trim_start().strip_prefix("#line") {
+            let mut numbers = arguments
+                .split_whitespace()
+                .map(|number| number.parse::<u32>().ok());
+            if let Some(Some(number)) = numbers.next() {
+                current_line = number;
+                if let Some(Some(number)) = numbers.next() {
+                    current_string = number;
+                }
+                continue;
+            }
+        }
+        if current_string == source_string && current_line == line {
+            return Some(String::from(text));
+        }
+        current_line += 1;
+    }
+    None
+}
+
+fn split_location(line: &str) -> Option<(&str, &str)> {
+    let first = line.chars().next()?;
+    if !first.is_ascii_digit() {
+        return None;
+    }
+
+    let end = line.find("):").or_else(|| line.find(") :"))? + 1;
+    let location = &line[..end];
+    let rest = line[end..].trim_start().strip_prefix(':')?;
+    Some((location, rest))
+}
+
+fn is_severity_word(word: &str) -> bool {
+    let word = word.trim().to_ascii_lowercase();
+    word.starts_with("error") || word.starts_with("warning") || word.starts_with("info")
+}
+
+fn severity_from(text: &str) -> Severity {
+    let text = text.trim().to_ascii_lowercase();
+    if text.starts_with("warning") {
+        Severity::Warning
+    } else if text.starts_with("info") {
+        Severity::Info
+    } else {
+        Severity::Error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str =
+        "#version 330 core\nout vec4 FragColor;\nvoid main() {\n    FragColor = color;\n}";
+
+    #[test]
+    fn test_parse_mesa_log() {
+        let log = "0:4(17): error: `color' undeclared\n";
+        let messages = parse_info_log(log, Some(SOURCE));
+        assert_eq!(1, messages.len());
+        assert_eq!(Severity::Error, messages[0].severity);
+        assert_eq!(Some(4), messages[0].line);
+        assert_eq!(Some(17), messages[0].column);
+        assert_eq!("`color' undeclared", messages[0].text);
+        assert_eq!(
+            Some(String::from("    FragColor = color;")),
+            messages[0].source_line
+        );
+    }
+
+    #[test]
+    fn test_parse_nvidia_log() {
+        let log = "0(4) : error C1008: undefined variable \"color\"";
+        let messages = parse_info_log(log, Some(SOURCE));
+        assert_eq!(Some(4), messages[0].line);
+        assert_eq!(None, messages[0].column);
+        assert_eq!(Severity::Error, messages[0].severity);
+        assert_eq!("undefined variable \"color\"", messages[0].text);
+    }
+
+    #[test]
+    fn test_parse_amd_log() {
+        let log = "WARNING: 0:2: unused variable\nERROR: 0:4: 'color' : undeclared identifier";
+        let messages = parse_info_log(log, Some(SOURCE));
+        assert_eq!(2, messages.len());
+        assert_eq!(Severity::Warning, messages[0].severity);
+        assert_eq!(Some(2), messages[0].line);
+        assert_eq!(Severity::Error, messages[1].severity);
+        assert_eq!("'color' : undeclared identifier", messages[1].text);
+    }
+
+    #[test]
+    fn test_parse_unknown_format() {
+        let messages = parse_info_log("error: linking failed\n\n", None);
+        assert_eq!(1, messages.len());
+        assert_eq!(None, messages[0].line);
+        assert_eq!("error: linking failed", messages[0].text);
+    }
+
+    #[test]
+    fn test_source_line_follows_line_directives() {
+        let source = "#version 330 core\n#define TEXTURED \n#line 2 0\nout vec4 FragColor;\n\
+                      #line 1 1\nfloat shade;\n#line 3 0\nvoid main() {}";
+        let messages = parse_info_log("0:3(1): error: a\n1(1) : error C0000: b", Some(source));
+        assert_eq!(Some(0), messages[0].source_string);
+        assert_eq!(
+            Some(String::from("void main() {}")),
+            messages[0].source_line
+        );
+        assert_eq!(Some(1), messages[1].source_string);
+        assert_eq!(Some(String::from("float shade;")), messages[1].source_line);
+    }
+
+    #[test]
+    fn test_diagnostic_errors_only() {
+        let log = "0:2(1): warning: unused\n0:4(17): error: `color' undeclared";
+        let diagnostic = ShaderDiagnostic::compilation(ShaderType::Fragment, log, SOURCE);
+        assert_eq!(Some(ShaderType::Fragment), diagnostic.stage);
+        assert_eq!(1, diagnostic.errors().len());
+        assert!(diagnostic.to_string().starts_with("Fragment shader"));
+    }
+}
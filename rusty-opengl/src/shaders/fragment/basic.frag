@@ -0,0 +1,19 @@
+#version 330 core
+// Variants: TEXTURED
+out vec4 FragColor;
+
+in vec4 ourColor;
+#ifdef TEXTURED
+in vec2 TexCoord;
+
+uniform sampler2D ourTexture;
+#endif
+
+void main()
+{
+#ifdef TEXTURED
+    FragColor = texture(ourTexture, TexCoord) * ourColor;
+#else
+    FragColor = ourColor;
+#endif
+}
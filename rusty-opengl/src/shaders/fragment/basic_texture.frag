@@ -1,13 +0,0 @@
-#version 330 core
-out vec4 FragColor;
-
-in vec4 ourColor;
-in vec2 TexCoord;
-
-// texture sampler
-uniform sampler2D ourTexture;
-
-void main()
-{
-	FragColor = texture(ourTexture, TexCoord) * vec4(ourColor);
-}
\ No newline at end of file
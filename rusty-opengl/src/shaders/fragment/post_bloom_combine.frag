@@ -0,0 +1,15 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+uniform sampler2D originalTexture;
+uniform float intensity;
+
+void main()
+{
+    vec4 original = texture(originalTexture, TexCoord);
+    vec3 bloom = texture(screenTexture, TexCoord).rgb;
+    FragColor = vec4(original.rgb + bloom * intensity, original.a);
+}
@@ -0,0 +1,14 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+uniform float threshold;
+
+void main()
+{
+    vec4 color = texture(screenTexture, TexCoord);
+    float brightness = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+    FragColor = brightness > threshold ? vec4(color.rgb, 1.0) : vec4(0.0, 0.0, 0.0, 1.0);
+}
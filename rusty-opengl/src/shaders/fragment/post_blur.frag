@@ -0,0 +1,24 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+uniform vec2 texelSize;
+uniform vec2 direction;
+uniform float radius;
+
+const float weights[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+// one direction of a separable 9-tap gaussian blur
+void main()
+{
+    vec2 step = direction * texelSize * radius;
+    vec4 result = texture(screenTexture, TexCoord) * weights[0];
+    for (int i = 1; i < 5; ++i)
+    {
+        result += texture(screenTexture, TexCoord + step * float(i)) * weights[i];
+        result += texture(screenTexture, TexCoord - step * float(i)) * weights[i];
+    }
+    FragColor = result;
+}
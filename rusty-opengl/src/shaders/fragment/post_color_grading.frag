@@ -0,0 +1,29 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+// size * size wide strip of size slices, red along x, green along y and
+// blue selecting the slice
+uniform sampler2D lutTexture;
+uniform float lutSize;
+uniform float intensity;
+
+vec3 lookup(vec3 color)
+{
+    float blue = clamp(color.b, 0.0, 1.0) * (lutSize - 1.0);
+    float slice = floor(blue);
+    float nextSlice = min(slice + 1.0, lutSize - 1.0);
+    float x = (clamp(color.r, 0.0, 1.0) * (lutSize - 1.0) + 0.5) / (lutSize * lutSize);
+    float y = (clamp(color.g, 0.0, 1.0) * (lutSize - 1.0) + 0.5) / lutSize;
+    vec3 first = texture(lutTexture, vec2(x + slice / lutSize, y)).rgb;
+    vec3 second = texture(lutTexture, vec2(x + nextSlice / lutSize, y)).rgb;
+    return mix(first, second, blue - slice);
+}
+
+void main()
+{
+    vec4 color = texture(screenTexture, TexCoord);
+    FragColor = vec4(mix(color.rgb, lookup(color.rgb), intensity), color.a);
+}
@@ -0,0 +1,41 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+uniform vec2 texelSize;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+const vec3 LUMA = vec3(0.299, 0.587, 0.114);
+
+// simplified FXAA, blurs along the edge direction found from the luminance
+void main()
+{
+    float lumaNW = dot(texture(screenTexture, TexCoord + vec2(-1.0, -1.0) * texelSize).rgb, LUMA);
+    float lumaNE = dot(texture(screenTexture, TexCoord + vec2(1.0, -1.0) * texelSize).rgb, LUMA);
+    float lumaSW = dot(texture(screenTexture, TexCoord + vec2(-1.0, 1.0) * texelSize).rgb, LUMA);
+    float lumaSE = dot(texture(screenTexture, TexCoord + vec2(1.0, 1.0) * texelSize).rgb, LUMA);
+    vec4 center = texture(screenTexture, TexCoord);
+    float lumaM = dot(center.rgb, LUMA);
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 direction = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)),
+                          (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float reduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * scale, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texelSize;
+
+    vec3 near = 0.5 * (texture(screenTexture, TexCoord + direction * (1.0 / 3.0 - 0.5)).rgb
+                     + texture(screenTexture, TexCoord + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 far = near * 0.5 + 0.25 * (texture(screenTexture, TexCoord - direction * 0.5).rgb
+                                  + texture(screenTexture, TexCoord + direction * 0.5).rgb);
+    float lumaFar = dot(far, LUMA);
+
+    vec3 result = (lumaFar < lumaMin || lumaFar > lumaMax) ? near : far;
+    FragColor = vec4(result, center.a);
+}
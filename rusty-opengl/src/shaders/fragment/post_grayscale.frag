@@ -0,0 +1,14 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+uniform float intensity;
+
+void main()
+{
+    vec4 color = texture(screenTexture, TexCoord);
+    float luminance = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+    FragColor = vec4(mix(color.rgb, vec3(luminance), intensity), color.a);
+}
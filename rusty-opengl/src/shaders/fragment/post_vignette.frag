@@ -0,0 +1,17 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoord;
+
+uniform sampler2D screenTexture;
+uniform float radius;
+uniform float softness;
+uniform float strength;
+
+void main()
+{
+    vec4 color = texture(screenTexture, TexCoord);
+    float distance = length(TexCoord - vec2(0.5));
+    float vignette = smoothstep(radius + softness, radius, distance);
+    FragColor = vec4(color.rgb * mix(1.0, vignette, strength), color.a);
+}
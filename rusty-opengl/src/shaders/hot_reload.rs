@@ -0,0 +1,133 @@
+use crate::error::Error;
+use crate::shaders::shader_program::ShaderProgram;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Detects changes of files by polling their modification time and size
+#[derive(Clone, Debug)]
+pub struct FileWatcher {
+    paths: Vec<PathBuf>,
+    stamps: Vec<Option<(SystemTime, u64)>>,
+}
+
+/// Rebuilds a shader program in place when its source files change, so the
+/// program can stay inside a `Polygon` or a post-processing `Pass`.
+///
+/// The sources replace the current program only if they compile and link,
+/// otherwise the current one keeps being used and the error is reported.
+/// Uniform values are carried over to the new program, see
+/// `ShaderProgram::reload_from`.
+pub struct HotReload {
+    vertex_path: PathBuf,
+    fragment_path: PathBuf,
+    watcher: FileWatcher,
+}
+
+impl FileWatcher {
+    /// The current state of the files is the baseline for `poll`
+    #[must_use]
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let stamps = paths.iter().map(|path| read_stamp(path)).collect();
+        FileWatcher { paths, stamps }
+    }
+
+    /// Returns `true` if any file was modified, created or removed since the
+    /// previous call
+    pub fn poll(&mut self) -> bool {
+        let mut result = false;
+        for (path, stamp) in self.paths.iter().zip(&mut self.stamps) {
+            let current = read_stamp(path);
+            if current != *stamp {
+                *stamp = current;
+                result = true;
+            }
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn get_paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+impl HotReload {
+    /// The current state of the files is the baseline for `reload_if_changed`
+    #[must_use]
+    pub fn new(vertex_path: &Path, fragment_path: &Path) -> Self {
+        HotReload {
+            vertex_path: vertex_path.to_path_buf(),
+            fragment_path: fragment_path.to_path_buf(),
+            watcher: FileWatcher::new(vec![vertex_path.to_path_buf(), fragment_path.to_path_buf()]),
+        }
+    }
+
+    /// Compiled program from the watched files, e.g. for
+    /// `PolygonBuilder::set_shader_program`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a source file could not be read or the program
+    /// could not be compiled or linked
+    pub fn load(&self) -> Result<ShaderProgram, Error> {
+        let mut result = ShaderProgram::from_files(&self.vertex_path, &self.fragment_path)?;
+        result.compile()?;
+        Ok(result)
+    }
+
+    /// Call it once per frame. Returns `None` if the files did not change,
+    /// otherwise the result of `reload`.
+    pub fn reload_if_changed(&mut self, program: &mut ShaderProgram) -> Option<Result<(), Error>> {
+        if self.watcher.poll() {
+            Some(self.reload(program))
+        } else {
+            None
+        }
+    }
+
+    /// Rebuilds `program` from the files with `ShaderProgram::reload_from`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a source file could not be read or the program
+    /// could not be compiled or linked, `program` stays unchanged then
+    pub fn reload(&self, program: &mut ShaderProgram) -> Result<(), Error> {
+        program.reload_from(&self.vertex_path, &self.fragment_path)
+    }
+
+    #[must_use]
+    pub fn get_watched_paths(&self) -> &[PathBuf] {
+        self.watcher.get_paths()
+    }
+}
+
+/// `None` if the file does not exist
+fn read_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::utils::unique_temp_path;
+    use std::fs::{self, File};
+    use std::time::Duration;
+
+    #[test]
+    fn test_file_watcher_detects_changes() {
+        let path = unique_temp_path("file_watcher_test.glsl");
+        fs::write(&path, "void main() {}").unwrap();
+        let mut watcher = FileWatcher::new(vec![path.clone()]);
+        assert!(!watcher.poll());
+
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watcher.poll());
+    }
+}
@@ -0,0 +1,386 @@
+use crate::error::Error;
+use crate::shaders::utils::read_src_from_file;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Set of `#define`s a shader variant is built with. Ordered by name, so
+/// equal sets compare and hash equally regardless of insertion order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderDefines {
+    values: BTreeMap<String, String>,
+}
+
+/// Output of the preprocessor. Source string `n` in driver messages refers
+/// to `files[n]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreprocessedSource {
+    pub source: String,
+    pub files: Vec<String>,
+}
+
+/// Resolves `#include "name"` and injects `#define`s after `#version`.
+///
+/// Includes are looked up next to the including file, then in the search
+/// paths in order, then among the registered includes. Every file is
+/// included at most once. `#line` directives follow the GLSL 3.30 rules.
+#[derive(Clone, Debug, Default)]
+pub struct Preprocessor {
+    search_paths: Vec<PathBuf>,
+    includes: HashMap<String, String>,
+}
+
+struct Include {
+    /// Identifies the include for the once-only check
+    key: String,
+    name: String,
+    directory: Option<PathBuf>,
+    source: String,
+}
+
+struct Expansion<'a> {
+    preprocessor: &'a Preprocessor,
+    output: String,
+    files: Vec<String>,
+    included: HashSet<String>,
+}
+
+impl ShaderDefines {
+    #[must_use]
+    pub fn new() -> Self {
+        ShaderDefines::default()
+    }
+
+    /// Defines `name` without a value, for `#ifdef` checks
+    pub fn set_flag(&mut self, name: &str) {
+        self.set(name, "");
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(String::from(name), String::from(value));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.values.remove(name);
+    }
+
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+/// Flags without values, e.g. `["TEXTURED", "VERTEX_COLOR"]`
+impl<S: AsRef<str>> FromIterator<S> for ShaderDefines {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut result = ShaderDefines::new();
+        for name in iter {
+            result.set_flag(name.as_ref());
+        }
+        result
+    }
+}
+
+impl Preprocessor {
+    #[must_use]
+    pub fn new() -> Self {
+        Preprocessor::default()
+    }
+
+    pub fn add_search_path(&mut self, path: PathBuf) {
+        self.search_paths.push(path);
+    }
+
+    #[must_use]
+    pub fn get_search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    /// Makes `source` available as `#include "name"` without a file
+    pub fn register_include(&mut self, name: &str, source: &str) {
+        self.includes
+            .insert(String::from(name), String::from(source));
+    }
+
+    /// `name` identifies the source in errors and as source string 0
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an `#include` is malformed, could not be found
+    /// or could not be read
+    pub fn process(
+        &self,
+        name: &str,
+        source: &str,
+        defines: &ShaderDefines,
+    ) -> Result<PreprocessedSource, Error> {
+        let mut expansion = Expansion {
+            preprocessor: self,
+            output: String::new(),
+            files: vec![],
+            included: HashSet::new(),
+        };
+        expansion.included.insert(String::from(name));
+        expansion.expand(name, None, source, Some(defines))?;
+        Ok(PreprocessedSource {
+            source: expansion.output,
+            files: expansion.files,
+        })
+    }
+
+    /// Like `process`, includes are looked up next to `path` first
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` or an include could not be read, or an
+    /// `#include` is malformed
+    pub fn process_file(
+        &self,
+        path: &Path,
+        defines: &ShaderDefines,
+    ) -> Result<PreprocessedSource, Error> {
+        let source = read_src_from_file(path)?;
+        let name = path.display().to_string();
+        let mut expansion = Expansion {
+            preprocessor: self,
+            output: String::new(),
+            files: vec![],
+            included: HashSet::new(),
+        };
+        expansion.included.insert(file_key(path));
+        expansion.expand(&name, path.parent(), &source, Some(defines))?;
+        Ok(PreprocessedSource {
+            source: expansion.output,
+            files: expansion.files,
+        })
+    }
+
+    fn resolve(&self, name: &str, directory: Option<&Path>) -> Result<Option<Include>, Error> {
+        let candidates = directory
+            .into_iter()
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .map(|directory| directory.join(name));
+        for path in candidates {
+            if path.is_file() {
+                return Ok(Some(Include {
+                    key: file_key(&path),
+                    name: path.display().to_string(),
+                    directory: path.parent().map(Path::to_path_buf),
+                    source: read_src_from_file(&path)?,
+                }));
+            }
+        }
+        Ok(self.includes.get(name).map(|source| Include {
+            key: String::from(name),
+            name: String::from(name),
+            directory: None,
+            source: source.clone(),
+        }))
+    }
+}
+
+impl Expansion<'_> {
+    /// `defines` are injected only into the top-level source
+    fn expand(
+        &mut self,
+        name: &str,
+        directory: Option<&Path>,
+        source: &str,
+        defines: Option<&ShaderDefines>,
+    ) -> Result<(), Error> {
+        let index = self.files.len();
+        self.files.push(String::from(name));
+
+        let mut lines = source.lines().enumerate().peekable();
+        if let Some(defines) = defines {
+            let has_version = source
+                .lines()
+                .any(|line| line.trim_start().starts_with("#version"));
+            if has_version {
+                for (_, line) in lines.by_ref() {
+                    self.output.push_str(line);
+                    self.output.push('\n');
+                    if line.trim_start().starts_with("#version") {
+                        break;
+                    }
+                }
+            }
+            for (define, value) in defines.iter() {
+                let _ = writeln!(self.output, "#define {define} {value}");
+            }
+            let next_line = lines.peek().map_or(1, |(number, _)| number + 1);
+            let _ = writeln!(self.output, "#line {next_line} {index}");
+        } else {
+            let _ = writeln!(self.output, "#line 1 {index}");
+        }
+
+        for (number, line) in lines {
+            let directive = line.trim_start();
+            if defines.is_none() && directive.starts_with("#version") {
+                // only the top-level source may declare the version
+                self.output.push('\n');
+            } else if let Some(arguments) = directive.strip_prefix("#include") {
+                let include_name = parse_include(arguments).ok_or_else(|| {
+                    Error::ShaderPreprocess(format!("{name}:{}: malformed #include", number + 1))
+                })?;
+                let include = self
+                    .preprocessor
+                    .resolve(include_name, directory)?
+                    .ok_or_else(|| {
+                        Error::ShaderPreprocess(format!(
+                            "{name}:{}: include \"{include_name}\" not found",
+                            number + 1
+                        ))
+                    })?;
+                if self.included.insert(include.key) {
+                    self.expand(
+                        &include.name,
+                        include.directory.as_deref(),
+                        &include.source,
+                        None,
+                    )?;
+                    let _ = writeln!(self.output, "#line {} {index}", number + 2);
+                } else {
+                    self.output.push('\n');
+                }
+            } else {
+                self.output.push_str(line);
+                self.output.push('\n');
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `"name"` or `<name>`
+fn parse_include(arguments: &str) -> Option<&str> {
+    let arguments = arguments.trim();
+    let closing = match arguments.chars().next()? {
+        '"' => '"',
+        '<' => '>',
+        _ => return None,
+    };
+    let (name, rest) = arguments[1..].split_once(closing)?;
+    let rest = rest.trim_start();
+    if name.is_empty() || !(rest.is_empty() || rest.starts_with("//")) {
+        return None;
+    }
+    Some(name)
+}
+
+fn file_key(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::utils::unique_temp_path;
+    use std::fs;
+
+    #[test]
+    fn test_defines_after_version() {
+        let defines: ShaderDefines = ["TEXTURED"].into_iter().collect();
+        let mut with_value = defines.clone();
+        with_value.set("LIGHTS", "4");
+
+        let result = Preprocessor::new()
+            .process(
+                "basic.frag",
+                "// header\n#version 330 core\nvoid main() {}",
+                &with_value,
+            )
+            .unwrap();
+        assert_eq!(
+            "// header\n#version 330 core\n#define LIGHTS 4\n#define TEXTURED \n#line 3 0\nvoid main() {}\n",
+            result.source
+        );
+        assert_eq!(vec![String::from("basic.frag")], result.files);
+
+        let mut reordered = ShaderDefines::new();
+        reordered.set("LIGHTS", "4");
+        reordered.set_flag("TEXTURED");
+        assert_eq!(with_value, reordered);
+        assert_ne!(defines, reordered);
+    }
+
+    #[test]
+    fn test_registered_includes_once() {
+        let mut preprocessor = Preprocessor::new();
+        preprocessor.register_include(
+            "common.glsl",
+            "#version 330 core\nfloat one() { return 1.0; }",
+        );
+        preprocessor.register_include(
+            "other.glsl",
+            "#include \"common.glsl\"\nfloat two() { return 2.0; }",
+        );
+
+        let result = preprocessor
+            .process(
+                "main.frag",
+                "#version 330 core\n#include <other.glsl>\n#include \"common.glsl\"\nvoid main() {}",
+                &ShaderDefines::new(),
+            )
+            .unwrap();
+        assert_eq!(
+            "#version 330 core\n#line 2 0\n\
+             #line 1 1\n#line 1 2\n\nfloat one() { return 1.0; }\n#line 2 1\nfloat two() { return 2.0; }\n#line 3 0\n\
+             \nvoid main() {}\n",
+            result.source
+        );
+        assert_eq!(vec!["main.frag", "other.glsl", "common.glsl"], result.files);
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let preprocessor = Preprocessor::new();
+        let defines = ShaderDefines::new();
+        let missing = preprocessor.process("a.frag", "\n#include \"missing.glsl\"", &defines);
+        assert!(
+            matches!(missing, Err(Error::ShaderPreprocess(reason)) if reason.starts_with("a.frag:2:"))
+        );
+        assert!(preprocessor
+            .process("a.frag", "#include missing.glsl", &defines)
+            .is_err());
+        assert!(preprocessor
+            .process("a.frag", "#include \"a.glsl\" extra", &defines)
+            .is_err());
+    }
+
+    #[test]
+    fn test_process_file_includes_relative_paths() {
+        let directory = unique_temp_path("preprocessor_test");
+        fs::create_dir_all(directory.join("lib")).unwrap();
+        fs::write(directory.join("main.frag"), "#include \"lib/a.glsl\"\n").unwrap();
+        fs::write(directory.join("lib/a.glsl"), "#include \"b.glsl\"\n").unwrap();
+        fs::write(directory.join("lib/b.glsl"), "float b;\n").unwrap();
+
+        let result = Preprocessor::new()
+            .process_file(&directory.join("main.frag"), &ShaderDefines::new())
+            .unwrap();
+        assert!(result.source.contains("float b;"));
+        assert_eq!(3, result.files.len());
+
+        assert!(fs::remove_dir_all(directory).is_ok());
+    }
+}
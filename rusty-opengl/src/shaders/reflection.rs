@@ -0,0 +1,574 @@
+use crate::error::Error;
+use crate::polygons::layout::VertexLayout;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt;
+
+/// Type of an active uniform or attribute as reported by the driver
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlslType {
+    /// `float` or `vecN` with the number of components
+    Float(u8),
+    Int(u8),
+    UInt(u8),
+    Bool(u8),
+    /// `matCxR` with columns and rows
+    Matrix(u8, u8),
+    /// Any sampler, with the GL enum of its type
+    Sampler(u32),
+    Other(u32),
+}
+
+/// Uniform of a linked program. Members of uniform blocks have no location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformInfo {
+    /// Name without the `[0]` suffix of arrays
+    pub name: String,
+    pub glsl_type: GlslType,
+    /// 1 for uniforms that are not arrays
+    pub array_size: u32,
+    pub location: Option<i32>,
+    /// Location of every element of arrays with a location, empty for other
+    /// uniforms. The driver does not have to make them consecutive.
+    pub element_locations: Vec<i32>,
+    pub block: Option<String>,
+}
+
+/// Vertex shader input of a linked program. Built-in inputs such as
+/// `gl_VertexID` have no location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeInfo {
+    pub name: String,
+    pub glsl_type: GlslType,
+    pub array_size: u32,
+    pub location: Option<u32>,
+}
+
+/// Active uniforms and attributes of a linked program, in driver order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramReflection {
+    uniforms: Vec<UniformInfo>,
+    attributes: Vec<AttributeInfo>,
+}
+
+const SAMPLER_NAMES: [(u32, &str); 19] = [
+    (gl::SAMPLER_1D, "sampler1D"),
+    (gl::SAMPLER_2D, "sampler2D"),
+    (gl::SAMPLER_3D, "sampler3D"),
+    (gl::SAMPLER_CUBE, "samplerCube"),
+    (gl::SAMPLER_1D_SHADOW, "sampler1DShadow"),
+    (gl::SAMPLER_2D_SHADOW, "sampler2DShadow"),
+    (gl::SAMPLER_1D_ARRAY, "sampler1DArray"),
+    (gl::SAMPLER_2D_ARRAY, "sampler2DArray"),
+    (gl::SAMPLER_2D_ARRAY_SHADOW, "sampler2DArrayShadow"),
+    (gl::SAMPLER_CUBE_SHADOW, "samplerCubeShadow"),
+    (gl::SAMPLER_2D_MULTISAMPLE, "sampler2DMS"),
+    (gl::SAMPLER_2D_RECT, "sampler2DRect"),
+    (gl::SAMPLER_BUFFER, "samplerBuffer"),
+    (gl::INT_SAMPLER_2D, "isampler2D"),
+    (gl::INT_SAMPLER_3D, "isampler3D"),
+    (gl::INT_SAMPLER_2D_ARRAY, "isampler2DArray"),
+    (gl::UNSIGNED_INT_SAMPLER_2D, "usampler2D"),
+    (gl::UNSIGNED_INT_SAMPLER_3D, "usampler3D"),
+    (gl::UNSIGNED_INT_SAMPLER_2D_ARRAY, "usampler2DArray"),
+];
+
+impl GlslType {
+    #[must_use]
+    pub fn from_gl(gl_type: u32) -> Self {
+        match gl_type {
+            gl::FLOAT => GlslType::Float(1),
+            gl::FLOAT_VEC2 => GlslType::Float(2),
+            gl::FLOAT_VEC3 => GlslType::Float(3),
+            gl::FLOAT_VEC4 => GlslType::Float(4),
+            gl::INT => GlslType::Int(1),
+            gl::INT_VEC2 => GlslType::Int(2),
+            gl::INT_VEC3 => GlslType::Int(3),
+            gl::INT_VEC4 => GlslType::Int(4),
+            gl::UNSIGNED_INT => GlslType::UInt(1),
+            gl::UNSIGNED_INT_VEC2 => GlslType::UInt(2),
+            gl::UNSIGNED_INT_VEC3 => GlslType::UInt(3),
+            gl::UNSIGNED_INT_VEC4 => GlslType::UInt(4),
+            gl::BOOL => GlslType::Bool(1),
+            gl::BOOL_VEC2 => GlslType::Bool(2),
+            gl::BOOL_VEC3 => GlslType::Bool(3),
+            gl::BOOL_VEC4 => GlslType::Bool(4),
+            gl::FLOAT_MAT2 => GlslType::Matrix(2, 2),
+            gl::FLOAT_MAT3 => GlslType::Matrix(3, 3),
+            gl::FLOAT_MAT4 => GlslType::Matrix(4, 4),
+            gl::FLOAT_MAT2x3 => GlslType::Matrix(2, 3),
+            gl::FLOAT_MAT2x4 => GlslType::Matrix(2, 4),
+            gl::FLOAT_MAT3x2 => GlslType::Matrix(3, 2),
+            gl::FLOAT_MAT3x4 => GlslType::Matrix(3, 4),
+            gl::FLOAT_MAT4x2 => GlslType::Matrix(4, 2),
+            gl::FLOAT_MAT4x3 => GlslType::Matrix(4, 3),
+            other if SAMPLER_NAMES.iter().any(|(sampler, _)| *sampler == other) => {
+                GlslType::Sampler(other)
+            }
+            other => GlslType::Other(other),
+        }
+    }
+
+    /// Scalars per element, e.g. 3 for `vec3` and 16 for `mat4`
+    #[must_use]
+    pub fn get_components(self) -> usize {
+        match self {
+            GlslType::Float(size)
+            | GlslType::Int(size)
+            | GlslType::UInt(size)
+            | GlslType::Bool(size) => usize::from(size),
+            GlslType::Matrix(columns, rows) => usize::from(columns) * usize::from(rows),
+            GlslType::Sampler(_) | GlslType::Other(_) => 1,
+        }
+    }
+
+    /// Attribute locations taken by one element, one per matrix column
+    #[must_use]
+    pub fn get_location_count(self) -> u32 {
+        match self {
+            GlslType::Matrix(columns, _) => u32::from(columns),
+            _ => 1,
+        }
+    }
+
+    #[must_use]
+    pub fn is_sampler(self) -> bool {
+        matches!(self, GlslType::Sampler(_))
+    }
+
+    /// Integer attributes are fed with `glVertexAttribIPointer`
+    #[must_use]
+    pub fn is_integer(self) -> bool {
+        matches!(
+            self,
+            GlslType::Int(_) | GlslType::UInt(_) | GlslType::Bool(_)
+        )
+    }
+}
+
+impl fmt::Display for GlslType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlslType::Float(1) => write!(f, "float"),
+            GlslType::Int(1) => write!(f, "int"),
+            GlslType::UInt(1) => write!(f, "uint"),
+            GlslType::Bool(1) => write!(f, "bool"),
+            GlslType::Float(size) => write!(f, "vec{size}"),
+            GlslType::Int(size) => write!(f, "ivec{size}"),
+            GlslType::UInt(size) => write!(f, "uvec{size}"),
+            GlslType::Bool(size) => write!(f, "bvec{size}"),
+            GlslType::Matrix(columns, rows) if columns == rows => write!(f, "mat{columns}"),
+            GlslType::Matrix(columns, rows) => write!(f, "mat{columns}x{rows}"),
+            GlslType::Sampler(sampler) => {
+                let name = SAMPLER_NAMES
+                    .iter()
+                    .find(|(gl_type, _)| gl_type == sampler)
+                    .map_or("sampler", |(_, name)| name);
+                write!(f, "{name}")
+            }
+            GlslType::Other(gl_type) => write!(f, "type 0x{gl_type:04X}"),
+        }
+    }
+}
+
+impl ProgramReflection {
+    #[must_use]
+    pub fn get_uniforms(&self) -> &[UniformInfo] {
+        &self.uniforms
+    }
+
+    #[must_use]
+    pub fn get_uniform(&self, name: &str) -> Option<&UniformInfo> {
+        self.uniforms.iter().find(|uniform| uniform.name == name)
+    }
+
+    #[must_use]
+    pub fn get_attributes(&self) -> &[AttributeInfo] {
+        &self.attributes
+    }
+
+    #[must_use]
+    pub fn get_attribute(&self, name: &str) -> Option<&AttributeInfo> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Checks that `layout` feeds every active attribute of the program
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an attribute location is not provided by the
+    /// layout, or is provided as an integer type for a float attribute or
+    /// the other way around
+    pub fn validate_layout(&self, layout: &VertexLayout) -> Result<(), Error> {
+        for attribute in &self.attributes {
+            let Some(location) = attribute.location else {
+                continue;
+            };
+
+            let locations = attribute.glsl_type.get_location_count() * attribute.array_size.max(1);
+            for location in location..location + locations {
+                let Some(provided) = layout
+                    .get_attributes()
+                    .iter()
+                    .find(|provided| provided.location == location)
+                else {
+                    return Err(Error::InvalidVertexLayout(format!(
+                        "shader expects attribute {} ({}) at location {location}, the vertices do not provide it",
+                        attribute.name, attribute.glsl_type
+                    )));
+                };
+                if provided.attribute_type.is_integer() != attribute.glsl_type.is_integer() {
+                    return Err(Error::InvalidVertexLayout(format!(
+                        "shader attribute {} is {}, the vertices provide {:?} at location {location}",
+                        attribute.name, attribute.glsl_type, provided.attribute_type
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Name, location and type of every uniform outside blocks, arrays are
+    /// listed element by element as `name[i]`
+    pub(crate) fn get_uniform_elements(&self) -> Vec<(String, i32, GlslType)> {
+        let mut result = vec![];
+        for uniform in &self.uniforms {
+            let Some(location) = uniform.location else {
+                continue;
+            };
+            if uniform.element_locations.is_empty() {
+                result.push((uniform.name.clone(), location, uniform.glsl_type));
+            }
+            for (index, element_location) in uniform.element_locations.iter().enumerate() {
+                result.push((
+                    format!("{}[{index}]", uniform.name),
+                    *element_location,
+                    uniform.glsl_type,
+                ));
+            }
+        }
+        result
+    }
+
+    /// Locations for `set_uniform`, arrays are found by their name, which
+    /// is their first element, and by `name[i]` for every element
+    pub(crate) fn get_uniform_locations(&self) -> HashMap<String, i32> {
+        let mut result = HashMap::new();
+        for uniform in &self.uniforms {
+            let Some(location) = uniform.location else {
+                continue;
+            };
+            for (index, element_location) in uniform.element_locations.iter().enumerate() {
+                result.insert(format!("{}[{index}]", uniform.name), *element_location);
+            }
+            result.insert(uniform.name.clone(), location);
+        }
+        result
+    }
+
+    /// Active resources of a linked program
+    pub(crate) unsafe fn query(program_id: u32) -> Self {
+        ProgramReflection {
+            uniforms: query_uniforms(program_id),
+            attributes: query_attributes(program_id),
+        }
+    }
+}
+
+type GetActiveResource = unsafe fn(
+    u32,
+    u32,
+    gl::types::GLsizei,
+    *mut gl::types::GLsizei,
+    *mut gl::types::GLint,
+    *mut gl::types::GLenum,
+    *mut gl::types::GLchar,
+);
+
+unsafe fn query_uniforms(program_id: u32) -> Vec<UniformInfo> {
+    let count = get_program_parameter(program_id, gl::ACTIVE_UNIFORMS);
+    let max_length = get_program_parameter(program_id, gl::ACTIVE_UNIFORM_MAX_LENGTH);
+
+    let mut result = vec![];
+    for index in 0..count {
+        let (name, array_size, gl_type) =
+            read_active_resource(gl::GetActiveUniform, program_id, index, max_length);
+        let mut block_index = -1;
+        gl::GetActiveUniformsiv(
+            program_id,
+            1,
+            &index,
+            gl::UNIFORM_BLOCK_INDEX,
+            &mut block_index,
+        );
+        let block = u32::try_from(block_index)
+            .ok()
+            .map(|block_index| read_block_name(program_id, block_index));
+        let location = match block {
+            None => get_uniform_location(program_id, &name),
+            Some(_) => None,
+        };
+        let name = strip_array_suffix(name);
+        let element_locations = match location {
+            Some(_) if array_size > 1 => (0..array_size)
+                .map_while(|index| get_uniform_location(program_id, &format!("{name}[{index}]")))
+                .collect(),
+            _ => vec![],
+        };
+        result.push(UniformInfo {
+            name,
+            glsl_type: GlslType::from_gl(gl_type),
+            array_size,
+            location,
+            element_locations,
+            block,
+        });
+    }
+    result
+}
+
+unsafe fn get_uniform_location(program_id: u32, name: &str) -> Option<i32> {
+    let c_name = CString::new(name.as_bytes()).ok()?;
+    Some(gl::GetUniformLocation(program_id, c_name.as_ptr())).filter(|location| *location != -1)
+}
+
+unsafe fn query_attributes(program_id: u32) -> Vec<AttributeInfo> {
+    let count = get_program_parameter(program_id, gl::ACTIVE_ATTRIBUTES);
+    let max_length = get_program_parameter(program_id, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH);
+
+    let mut result = vec![];
+    for index in 0..count {
+        let (name, array_size, gl_type) =
+            read_active_resource(gl::GetActiveAttrib, program_id, index, max_length);
+        let location = CString::new(name.as_bytes())
+            .ok()
+            .map(|c_name| gl::GetAttribLocation(program_id, c_name.as_ptr()))
+            .and_then(|location| u32::try_from(location).ok());
+        result.push(AttributeInfo {
+            name: strip_array_suffix(name),
+            glsl_type: GlslType::from_gl(gl_type),
+            array_size,
+            location,
+        });
+    }
+    result
+}
+
+/// Name, array size and type of the resource at `index`
+unsafe fn read_active_resource(
+    get_active: GetActiveResource,
+    program_id: u32,
+    index: u32,
+    max_length: u32,
+) -> (String, u32, u32) {
+    let mut name: Vec<u8> = vec![0; max_length as usize + 1];
+    let mut written = 0;
+    let mut size = 0;
+    let mut gl_type = 0;
+    get_active(
+        program_id,
+        index,
+        name.len().try_into().unwrap_or(i32::MAX),
+        &mut written,
+        &mut size,
+        &mut gl_type,
+        name.as_mut_ptr().cast::<gl::types::GLchar>(),
+    );
+    name.truncate(written.try_into().unwrap_or(0));
+    (
+        String::from_utf8_lossy(&name).into_owned(),
+        u32::try_from(size).unwrap_or(1),
+        gl_type,
+    )
+}
+
+unsafe fn read_block_name(program_id: u32, block_index: u32) -> String {
+    let mut length = 0;
+    gl::GetActiveUniformBlockiv(
+        program_id,
+        block_index,
+        gl::UNIFORM_BLOCK_NAME_LENGTH,
+        &mut length,
+    );
+    let mut name: Vec<u8> = vec![0; usize::try_from(length).unwrap_or(0) + 1];
+    let mut written = 0;
+    gl::GetActiveUniformBlockName(
+        program_id,
+        block_index,
+        name.len().try_into().unwrap_or(i32::MAX),
+        &mut written,
+        name.as_mut_ptr().cast::<gl::types::GLchar>(),
+    );
+    name.truncate(written.try_into().unwrap_or(0));
+    String::from_utf8_lossy(&name).into_owned()
+}
+
+unsafe fn get_program_parameter(program_id: u32, parameter: u32) -> u32 {
+    let mut result = 0;
+    gl::GetProgramiv(program_id, parameter, &mut result);
+    u32::try_from(result).unwrap_or(0)
+}
+
+fn strip_array_suffix(mut name: String) -> String {
+    if name.ends_with("[0]") {
+        name.truncate(name.len() - 3);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polygons::layout::AttributeType;
+
+    fn attribute(name: &str, glsl_type: GlslType, location: Option<u32>) -> AttributeInfo {
+        AttributeInfo {
+            name: String::from(name),
+            glsl_type,
+            array_size: 1,
+            location,
+        }
+    }
+
+    fn texture_shader() -> ProgramReflection {
+        ProgramReflection {
+            uniforms: vec![UniformInfo {
+                name: String::from("ourTexture"),
+                glsl_type: GlslType::from_gl(gl::SAMPLER_2D),
+                array_size: 1,
+                location: Some(0),
+                element_locations: vec![],
+                block: None,
+            }],
+            attributes: vec![
+                attribute("aPos", GlslType::Float(3), Some(0)),
+                attribute("aColor", GlslType::Float(4), Some(1)),
+                attribute("aTexCoord", GlslType::Float(2), Some(2)),
+                attribute("gl_VertexID", GlslType::Int(1), None),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_glsl_types() {
+        assert_eq!(GlslType::Float(3), GlslType::from_gl(gl::FLOAT_VEC3));
+        assert_eq!("mat4", GlslType::from_gl(gl::FLOAT_MAT4).to_string());
+        assert_eq!("mat2x3", GlslType::from_gl(gl::FLOAT_MAT2x3).to_string());
+        assert_eq!(
+            "uvec2",
+            GlslType::from_gl(gl::UNSIGNED_INT_VEC2).to_string()
+        );
+        assert_eq!(16, GlslType::Matrix(4, 4).get_components());
+        assert!(GlslType::from_gl(gl::SAMPLER_2D).is_sampler());
+        assert_eq!("sampler2D", GlslType::from_gl(gl::SAMPLER_2D).to_string());
+        assert_eq!(GlslType::Other(gl::DOUBLE), GlslType::from_gl(gl::DOUBLE));
+        assert_eq!("lights", strip_array_suffix(String::from("lights[0]")));
+    }
+
+    #[test]
+    fn test_validate_layout_reports_missing_attribute() {
+        let reflection = texture_shader();
+        let colored = VertexLayout::new()
+            .with_attribute("aPos", 0, 3, AttributeType::F32)
+            .with_attribute("aColor", 1, 4, AttributeType::F32);
+        let error = reflection.validate_layout(&colored).unwrap_err();
+        assert!(error.to_string().contains("aTexCoord (vec2) at location 2"));
+
+        let textured = colored.with_attribute("aTexCoord", 2, 2, AttributeType::F32);
+        assert!(reflection.validate_layout(&textured).is_ok());
+        assert_eq!(
+            Some(0),
+            reflection.get_uniform("ourTexture").unwrap().location
+        );
+    }
+
+    #[test]
+    fn test_validate_layout_matrix_and_integer_attributes() {
+        let layout = VertexLayout::new()
+            .with_attribute("aModel0", 3, 4, AttributeType::F32)
+            .with_attribute("aModel1", 4, 4, AttributeType::F32);
+        let mut reflection = ProgramReflection {
+            uniforms: vec![],
+            attributes: vec![attribute("aModel", GlslType::Matrix(4, 4), Some(3))],
+        };
+        assert!(reflection.validate_layout(&layout).is_err());
+
+        reflection.attributes[0].glsl_type = GlslType::Matrix(2, 4);
+        assert!(reflection.validate_layout(&layout).is_ok());
+
+        reflection.attributes[0] = attribute("aId", GlslType::Int(1), Some(3));
+        assert!(reflection.validate_layout(&layout).is_err());
+
+        let integer_layout = VertexLayout::new().with_attribute("aId", 3, 1, AttributeType::U16);
+        assert!(reflection.validate_layout(&integer_layout).is_ok());
+
+        reflection.attributes[0].glsl_type = GlslType::Float(1);
+        let error = reflection.validate_layout(&integer_layout).unwrap_err();
+        assert!(error.to_string().contains("provide U16 at location 3"));
+    }
+
+    #[test]
+    fn test_uniform_locations_from_reflection() {
+        let mut reflection = texture_shader();
+        reflection.uniforms.push(UniformInfo {
+            name: String::from("lights"),
+            glsl_type: GlslType::Float(3),
+            array_size: 4,
+            location: Some(5),
+            element_locations: vec![5, 6, 7, 8],
+            block: None,
+        });
+        reflection.uniforms.push(UniformInfo {
+            name: String::from("scale"),
+            glsl_type: GlslType::Float(1),
+            array_size: 1,
+            location: None,
+            element_locations: vec![],
+            block: Some(String::from("Globals")),
+        });
+
+        let locations = reflection.get_uniform_locations();
+        assert_eq!(6, locations.len());
+        assert_eq!(Some(&0), locations.get("ourTexture"));
+        assert_eq!(Some(&5), locations.get("lights"));
+        assert_eq!(Some(&5), locations.get("lights[0]"));
+    }
+
+    #[test]
+    fn test_uniform_locations_of_array_elements() {
+        let reflection = ProgramReflection {
+            uniforms: vec![UniformInfo {
+                name: String::from("weights"),
+                glsl_type: GlslType::Float(1),
+                array_size: 3,
+                location: Some(2),
+                element_locations: vec![2, 9, 4],
+                block: None,
+            }],
+            attributes: vec![],
+        };
+
+        let locations = reflection.get_uniform_locations();
+        assert_eq!(4, locations.len());
+        assert_eq!(Some(&2), locations.get("weights"));
+        assert_eq!(Some(&9), locations.get("weights[1]"));
+        assert_eq!(Some(&4), locations.get("weights[2]"));
+        assert_eq!(None, locations.get("weights[3]"));
+
+        let elements: Vec<(String, i32)> = reflection
+            .get_uniform_elements()
+            .into_iter()
+            .map(|(name, location, _)| (name, location))
+            .collect();
+        assert_eq!(
+            vec![
+                (String::from("weights[0]"), 2),
+                (String::from("weights[1]"), 9),
+                (String::from("weights[2]"), 4)
+            ],
+            elements
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::error::Error;
+use crate::shaders::preprocessor::{Preprocessor, ShaderDefines};
+use crate::shaders::shader_program::{ShaderProgram, ShaderType};
+use crate::shaders::utils::read_src_from_file;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Shaders shipped with the library, embedded at compile time
+const BUILT_IN_SHADERS: [(&str, &str); 12] = [
+    ("basic.vert", include_str!("vertex/basic.vert")),
+    (
+        "colored_circle.vert",
+        include_str!("vertex/colored_circle.vert"),
+    ),
+    ("fullscreen.vert", include_str!("vertex/fullscreen.vert")),
+    ("basic.frag", include_str!("fragment/basic.frag")),
+    (
+        "post_bloom_combine.frag",
+        include_str!("fragment/post_bloom_combine.frag"),
+    ),
+    (
+        "post_bloom_extract.frag",
+        include_str!("fragment/post_bloom_extract.frag"),
+    ),
+    ("post_blur.frag", include_str!("fragment/post_blur.frag")),
+    (
+        "post_color_grading.frag",
+        include_str!("fragment/post_color_grading.frag"),
+    ),
+    ("post_copy.frag", include_str!("fragment/post_copy.frag")),
+    ("post_fxaa.frag", include_str!("fragment/post_fxaa.frag")),
+    (
+        "post_grayscale.frag",
+        include_str!("fragment/post_grayscale.frag"),
+    ),
+    (
+        "post_vignette.frag",
+        include_str!("fragment/post_vignette.frag"),
+    ),
+];
+
+/// Shader sources by file name, e.g. `basic.vert`.
+///
+/// A file in the override directory wins over a registered source, which
+/// wins over a built-in one. The override directory has the same layout as
+/// `src/shaders`: `vertex/<name>`, `fragment/<name>` and likewise
+/// `geometry`, `tess_control`, `tess_evaluation` and `compute`. Sources go
+/// through the registry's `Preprocessor` before they are handed to a program.
+#[derive(Clone, Debug)]
+pub struct ShaderRegistry {
+    sources: HashMap<String, String>,
+    override_directory: Option<PathBuf>,
+    preprocessor: Preprocessor,
+}
+
+thread_local! {
+    static SHADER_REGISTRY: RefCell<ShaderRegistry> = RefCell::new(ShaderRegistry::new());
+}
+
+impl ShaderRegistry {
+    /// Only the built-in shaders, without an override directory
+    #[must_use]
+    pub fn new() -> Self {
+        ShaderRegistry {
+            sources: BUILT_IN_SHADERS
+                .iter()
+                .map(|(name, source)| (String::from(*name), String::from(*source)))
+                .collect(),
+            override_directory: None,
+            preprocessor: Preprocessor::new(),
+        }
+    }
+
+    /// Adds a shader or replaces the source served for `name`
+    pub fn register(&mut self, name: &str, source: &str) {
+        self.sources
+            .insert(String::from(name), String::from(source));
+    }
+
+    pub fn set_override_directory(&mut self, directory: Option<PathBuf>) {
+        self.override_directory = directory;
+    }
+
+    #[must_use]
+    pub fn get_override_directory(&self) -> Option<&Path> {
+        self.override_directory.as_deref()
+    }
+
+    /// Search paths and includes used by the programs of this registry
+    #[must_use]
+    pub fn get_preprocessor(&self) -> &Preprocessor {
+        &self.preprocessor
+    }
+
+    pub fn get_preprocessor_mut(&mut self) -> &mut Preprocessor {
+        &mut self.preprocessor
+    }
+
+    /// Registered names in alphabetical order
+    #[must_use]
+    pub fn get_names(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = self.sources.keys().map(String::as_str).collect();
+        result.sort_unstable();
+        result
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if no source is registered for `name` or the
+    /// override file exists but could not be read
+    pub fn get_source(&self, shader_type: ShaderType, name: &str) -> Result<String, Error> {
+        if let Some(path) = self.get_override_path(shader_type, name) {
+            if path.is_file() {
+                return read_src_from_file(&path);
+            }
+        }
+        self.sources
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::ShaderSourceNotFound(PathBuf::from(name)))
+    }
+
+    /// Program that still has to be compiled
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the sources could not be found or
+    /// preprocessed
+    pub fn create_program(
+        &self,
+        vertex_name: &str,
+        fragment_name: &str,
+    ) -> Result<ShaderProgram, Error> {
+        self.create_variant(vertex_name, fragment_name, &ShaderDefines::new())
+    }
+
+    /// Program that still has to be compiled, with `defines` injected into
+    /// both stages. See `ShaderVariants` to cache the compiled programs.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the sources could not be found or
+    /// preprocessed
+    pub fn create_variant(
+        &self,
+        vertex_name: &str,
+        fragment_name: &str,
+        defines: &ShaderDefines,
+    ) -> Result<ShaderProgram, Error> {
+        let vertex_src = self.preprocess(ShaderType::Vertex, vertex_name, defines)?;
+        let fragment_src = self.preprocess(ShaderType::Fragment, fragment_name, defines)?;
+        Ok(ShaderProgram::new(&vertex_src, &fragment_src))
+    }
+
+    fn preprocess(
+        &self,
+        shader_type: ShaderType,
+        name: &str,
+        defines: &ShaderDefines,
+    ) -> Result<String, Error> {
+        if let Some(path) = self.get_override_path(shader_type, name) {
+            if path.is_file() {
+                return Ok(self.preprocessor.process_file(&path, defines)?.source);
+            }
+        }
+        let source = self.get_source(shader_type, name)?;
+        Ok(self.preprocessor.process(name, &source, defines)?.source)
+    }
+
+    fn get_override_path(&self, shader_type: ShaderType, name: &str) -> Option<PathBuf> {
+        let directory = match shader_type {
+            ShaderType::Vertex => "vertex",
+            ShaderType::TessControl => "tess_control",
+            ShaderType::TessEvaluation => "tess_evaluation",
+            ShaderType::Geometry => "geometry",
+            ShaderType::Fragment => "fragment",
+            ShaderType::Compute => "compute",
+        };
+        Some(self.override_directory.as_ref()?.join(directory).join(name))
+    }
+}
+
+impl Default for ShaderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs `f` with the registry used by the built-in types of the current
+/// thread, e.g. to set an override directory before building polygons
+pub fn with_shader_registry<R>(f: impl FnOnce(&mut ShaderRegistry) -> R) -> R {
+    SHADER_REGISTRY.with(|registry| f(&mut registry.borrow_mut()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::utils::unique_temp_path;
+    use std::fs;
+
+    #[test]
+    fn test_built_in_sources() {
+        let registry = ShaderRegistry::new();
+        let source = registry
+            .get_source(ShaderType::Vertex, "basic.vert")
+            .unwrap();
+        assert!(source.contains("viewProjection"));
+        assert!(registry.get_names().contains(&"colored_circle.vert"));
+        assert!(matches!(
+            registry.get_source(ShaderType::Fragment, "missing.frag"),
+            Err(Error::ShaderSourceNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_basic_variants() {
+        let registry = ShaderRegistry::new();
+        let defines: ShaderDefines = ["TEXTURED"].into_iter().collect();
+        let source = registry
+            .preprocess(ShaderType::Fragment, "basic.frag", &defines)
+            .unwrap();
+        assert!(source.starts_with("#version 330 core\n#define TEXTURED \n#line 2 0\n"));
+    }
+
+    #[test]
+    fn test_override_directory_wins() {
+        let directory = unique_temp_path("registry_test");
+        fs::create_dir_all(directory.join("fragment")).unwrap();
+        fs::write(directory.join("fragment/basic.frag"), "// file").unwrap();
+
+        let mut registry = ShaderRegistry::new();
+        registry.register("basic.frag", "// registered");
+        assert_eq!(
+            "// registered",
+            registry
+                .get_source(ShaderType::Fragment, "basic.frag")
+                .unwrap()
+        );
+
+        registry.set_override_directory(Some(directory.clone()));
+        assert_eq!(
+            "// file",
+            registry
+                .get_source(ShaderType::Fragment, "basic.frag")
+                .unwrap()
+        );
+        assert!(registry
+            .get_source(ShaderType::Vertex, "basic.vert")
+            .unwrap()
+            .contains("aPos"));
+
+        assert!(fs::remove_dir_all(directory).is_ok());
+    }
+}
@@ -1,32 +1,139 @@
 use crate::color::RGBA;
+use crate::error::Error;
+use crate::gl_state::{get_program, use_program};
+use crate::polygons::layout::VertexLayout;
+use crate::shaders::diagnostic::ShaderDiagnostic;
+use crate::shaders::reflection::ProgramReflection;
+use crate::shaders::uniform::{Uniform, UniformValue};
+use crate::shaders::utils::read_src_from_file;
+use std::collections::HashMap;
 use std::ffi::CString;
+use std::fmt;
+use std::path::Path;
 use std::ptr;
 
-#[derive(Copy, Clone)]
-enum ShaderType {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderType {
     Vertex,
+    TessControl,
+    TessEvaluation,
+    Geometry,
     Fragment,
+    Compute,
 }
 
+impl ShaderType {
+    /// Lowest OpenGL version with the stage in core profile
+    #[must_use]
+    pub fn get_required_version(self) -> (u32, u32) {
+        match self {
+            ShaderType::Vertex | ShaderType::Fragment => (2, 0),
+            ShaderType::Geometry => (3, 2),
+            ShaderType::TessControl | ShaderType::TessEvaluation => (4, 0),
+            ShaderType::Compute => (4, 3),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the stage is not available in a context of
+    /// `context_version`
+    pub fn check_support(self, context_version: (u32, u32)) -> Result<(), Error> {
+        let required = self.get_required_version();
+        if context_version >= required {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedFeature(format!(
+                "{self} shaders need OpenGL {}.{}, the context is {}.{}",
+                required.0, required.1, context_version.0, context_version.1
+            )))
+        }
+    }
+}
+
+impl fmt::Display for ShaderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderType::Vertex => write!(f, "Vertex"),
+            ShaderType::TessControl => write!(f, "Tessellation control"),
+            ShaderType::TessEvaluation => write!(f, "Tessellation evaluation"),
+            ShaderType::Geometry => write!(f, "Geometry"),
+            ShaderType::Fragment => write!(f, "Fragment"),
+            ShaderType::Compute => write!(f, "Compute"),
+        }
+    }
+}
+
+/// Optional stages in pipeline order
+const OPTIONAL_STAGES: [ShaderType; 3] = [
+    ShaderType::TessControl,
+    ShaderType::TessEvaluation,
+    ShaderType::Geometry,
+];
+
 pub struct ShaderProgram {
-    shader_program_id: u32,
+    program: LinkedProgram,
     vert_src: String,
     frag_src: String,
+    optional_srcs: HashMap<ShaderType, String>,
     is_compiled: bool,
 }
 
+/// GL program object with what is known about it after linking, shared by
+/// `ShaderProgram` and `ComputeProgram`
+pub(crate) struct LinkedProgram {
+    id: u32,
+    uniform_locations: HashMap<String, i32>,
+    reflection: ProgramReflection,
+}
+
 impl ShaderProgram {
     #[must_use]
     pub fn new(vert_src: &str, frag_src: &str) -> Self {
-        let id = unsafe { gl::CreateProgram() };
         ShaderProgram {
-            shader_program_id: id,
+            program: LinkedProgram::new(),
             vert_src: String::from(vert_src),
             frag_src: String::from(frag_src),
+            optional_srcs: HashMap::new(),
             is_compiled: false,
         }
     }
 
+    /// Program that still has to be compiled, see `HotReload` to rebuild it
+    /// when the files change
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any of the files could not be read
+    pub fn from_files(vertex_path: &Path, fragment_path: &Path) -> Result<Self, Error> {
+        let vert_src = read_src_from_file(vertex_path)?;
+        let frag_src = read_src_from_file(fragment_path)?;
+        Ok(ShaderProgram::new(&vert_src, &frag_src))
+    }
+
+    /// Rebuilds the program in place from the files, optional stages are
+    /// kept. Uniform values are copied to the new program for every uniform
+    /// that still exists with the same name and type, the others start from
+    /// their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file could not be read or the new sources could
+    /// not be compiled or linked, the current program stays in use then
+    pub fn reload_from(&mut self, vertex_path: &Path, fragment_path: &Path) -> Result<(), Error> {
+        let mut candidate = ShaderProgram::from_files(vertex_path, fragment_path)?;
+        candidate.optional_srcs = self.optional_srcs.clone();
+        candidate.compile()?;
+        candidate.program.copy_uniform_values(&self.program);
+
+        let is_active = get_program() == self.program.get_id();
+        std::mem::swap(self, &mut candidate);
+        if is_active {
+            self.activate();
+        }
+        Ok(())
+    }
+
     pub fn set_fragment_shader(&mut self, src: &str) {
         self.frag_src = String::from(src);
         self.is_compiled = false;
@@ -37,47 +144,103 @@ impl ShaderProgram {
         self.is_compiled = false;
     }
 
-    pub fn compile(&mut self) -> bool {
-        if self.is_compiled {
-            return true;
+    /// Requires OpenGL 3.2, checked by `compile`
+    pub fn set_geometry_shader(&mut self, src: &str) {
+        self.set_optional_shader(ShaderType::Geometry, src);
+    }
+
+    /// Requires OpenGL 4.0, checked by `compile`
+    pub fn set_tess_control_shader(&mut self, src: &str) {
+        self.set_optional_shader(ShaderType::TessControl, src);
+    }
+
+    /// Requires OpenGL 4.0, checked by `compile`. Draw calls must use
+    /// `gl::PATCHES`, see `set_patch_vertices`.
+    pub fn set_tess_evaluation_shader(&mut self, src: &str) {
+        self.set_optional_shader(ShaderType::TessEvaluation, src);
+    }
+
+    /// Removes a geometry or tessellation stage, other stages are kept
+    pub fn remove_shader(&mut self, shader_type: ShaderType) {
+        if self.optional_srcs.remove(&shader_type).is_some() {
+            self.is_compiled = false;
         }
+    }
+
+    #[must_use]
+    pub fn get_shader_source(&self, shader_type: ShaderType) -> Option<&str> {
+        self.match_shader_src(shader_type).map(String::as_str)
+    }
 
+    /// Number of vertices per patch for the following tessellated draw calls.
+    /// `compile` checked that the context supports tessellation.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the program is not compiled or has no
+    /// tessellation stage
+    pub fn set_patch_vertices(&self, count: i32) -> Result<(), Error> {
+        if !self.is_compiled {
+            return Err(Error::InvalidProgram(String::from(
+                "patch vertices need a compiled program",
+            )));
+        }
+        let is_tessellated = [ShaderType::TessControl, ShaderType::TessEvaluation]
+            .iter()
+            .any(|stage| self.optional_srcs.contains_key(stage));
+        if !is_tessellated {
+            return Err(Error::InvalidProgram(String::from(
+                "patch vertices need a tessellation stage",
+            )));
+        }
         unsafe {
-            let vertex_shader_id = self.compile_shader(ShaderType::Vertex);
-            if vertex_shader_id == 0 {
-                return false;
-            }
+            gl::PatchParameteri(gl::PATCH_VERTICES, count);
+        }
+        Ok(())
+    }
 
-            let fragment_shader_id = self.compile_shader(ShaderType::Fragment);
-            if fragment_shader_id == 0 {
-                return false;
-            }
+    /// # Errors
+    ///
+    /// Will return `Err` with a [`ShaderDiagnostic`] describing the failed stage
+    /// and the driver messages if any shader stage fails to compile or the
+    /// program fails to link, or `Error::UnsupportedFeature` if an optional
+    /// stage is not available in the current context
+    pub fn compile(&mut self) -> Result<(), Error> {
+        if self.is_compiled {
+            return Ok(());
+        }
 
-            gl::AttachShader(self.shader_program_id, vertex_shader_id);
-            gl::AttachShader(self.shader_program_id, fragment_shader_id);
-            gl::LinkProgram(self.shader_program_id);
-            let result = ShaderProgram::check_link_status(self.shader_program_id);
-            if !result {
-                return false;
+        let stages = self.get_stages();
+        if !self.optional_srcs.is_empty() {
+            let version = get_context_version();
+            for stage in &stages {
+                stage.check_support(version)?;
             }
-            gl::DeleteShader(vertex_shader_id);
-            gl::DeleteShader(fragment_shader_id);
         }
 
+        let sources: Vec<(ShaderType, &str)> = stages
+            .iter()
+            .map(|&stage| {
+                let src = match stage {
+                    ShaderType::Vertex => &self.vert_src,
+                    ShaderType::Fragment => &self.frag_src,
+                    _ => &self.optional_srcs[&stage],
+                };
+                (stage, src.as_str())
+            })
+            .collect();
+        self.program.link(&sources)?;
+
         self.is_compiled = true;
-        true
+        Ok(())
     }
 
     pub fn activate(&self) {
-        unsafe {
-            gl::UseProgram(self.shader_program_id);
-        }
+        self.program.activate();
     }
 
     pub fn deactivate() {
-        unsafe {
-            gl::UseProgram(0);
-        }
+        use_program(0);
     }
 
     #[must_use]
@@ -85,105 +248,327 @@ impl ShaderProgram {
         self.is_compiled
     }
 
-    /// # Panics
+    /// Uploads `value` to the uniform `name`. If another program is active,
+    /// this one is bound only for the duration of the call.
+    ///
+    /// # Errors
     ///
-    /// Will panic if provided string is invalid
+    /// Will return `Err` if the program is not linked or has no active uniform
+    /// with this name
+    pub fn set_uniform<T: Uniform + ?Sized>(&self, name: &str, value: &T) -> Result<(), Error> {
+        self.program.set_uniform(name, value)
+    }
+
+    /// Active uniforms and attributes after the last successful link
     #[must_use]
-    pub fn set_uniform4f_variable(&self, variable: &str, value: &RGBA) -> bool {
-        self.activate();
-        unsafe {
-            let c_variable = CString::new(variable).unwrap();
-            let uniform_location =
-                gl::GetUniformLocation(self.shader_program_id, c_variable.as_ptr());
-            if uniform_location == -1 {
-                ShaderProgram::deactivate();
-                return false;
-            }
+    pub fn get_reflection(&self) -> &ProgramReflection {
+        self.program.get_reflection()
+    }
 
-            let color = value.get_as_normalized_f32();
-            gl::Uniform4f(uniform_location, color[0], color[1], color[2], color[3]);
-            ShaderProgram::deactivate();
-            true
-        }
+    /// See `ProgramReflection::validate_layout`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `layout` does not feed the active attributes
+    pub fn validate_layout(&self, layout: &VertexLayout) -> Result<(), Error> {
+        self.program.get_reflection().validate_layout(layout)
     }
 
-    fn match_shader_src(&self, shader_type: ShaderType) -> &String {
-        match shader_type {
-            ShaderType::Vertex => &self.vert_src,
-            ShaderType::Fragment => &self.frag_src,
+    /// # Errors
+    ///
+    /// Will return `Err` for the first of `names` that is not an active
+    /// uniform of the linked program
+    pub fn require_uniforms(&self, names: &[&str]) -> Result<(), Error> {
+        match names
+            .iter()
+            .find(|name| self.get_uniform_location(name).is_none())
+        {
+            Some(name) => Err(Error::UniformNotFound(String::from(*name))),
+            None => Ok(()),
         }
     }
 
-    unsafe fn compile_shader(&self, shader_type: ShaderType) -> u32 {
-        let shader = gl::CreateShader(shader_type_as_id(shader_type));
-        let c_str_vert = CString::new(self.match_shader_src(shader_type).as_bytes()).unwrap();
-        gl::ShaderSource(shader, 1, &c_str_vert.as_ptr(), ptr::null());
-        gl::CompileShader(shader);
+    /// Location cached after the last successful link
+    #[must_use]
+    pub fn get_uniform_location(&self, name: &str) -> Option<i32> {
+        self.program.get_uniform_location(name)
+    }
 
-        let status = ShaderProgram::check_compile_status(shader);
-        if status {
-            shader
-        } else {
-            0
-        }
+    #[must_use]
+    pub fn set_uniform4f_variable(&self, variable: &str, value: &RGBA) -> bool {
+        self.set_uniform(variable, value).is_ok()
     }
 
-    unsafe fn check_compile_status(shader_id: u32) -> bool {
-        let mut status = i32::from(gl::TRUE);
-        let info_length: usize = 512;
-        let mut info_log: Vec<u8> = Vec::with_capacity(info_length - 1);
-        gl::GetShaderiv(shader_id, gl::COMPILE_STATUS, &mut status);
-        if status == i32::from(gl::TRUE) {
-            true
-        } else {
-            gl::GetShaderInfoLog(
-                shader_id,
-                info_length.try_into().unwrap(),
-                ptr::null_mut(),
-                info_log.as_mut_ptr().cast::<i8>(),
-            );
-            println!(
-                "Shader compilation error\n{}",
-                std::str::from_utf8(&info_log).unwrap()
-            );
-            false
+    fn set_optional_shader(&mut self, shader_type: ShaderType, src: &str) {
+        self.optional_srcs.insert(shader_type, String::from(src));
+        self.is_compiled = false;
+    }
+
+    fn match_shader_src(&self, shader_type: ShaderType) -> Option<&String> {
+        match shader_type {
+            ShaderType::Vertex => Some(&self.vert_src),
+            ShaderType::Fragment => Some(&self.frag_src),
+            _ => self.optional_srcs.get(&shader_type),
         }
     }
 
-    unsafe fn check_link_status(shader_program_id: u32) -> bool {
+    /// Stages with a source, in pipeline order
+    fn get_stages(&self) -> Vec<ShaderType> {
+        let mut result = vec![ShaderType::Vertex];
+        result.extend(
+            OPTIONAL_STAGES
+                .iter()
+                .filter(|stage| self.optional_srcs.contains_key(stage)),
+        );
+        result.push(ShaderType::Fragment);
+        result
+    }
+
+    unsafe fn check_link_status(shader_program_id: u32) -> Result<(), Error> {
         let mut status = i32::from(gl::FALSE);
-        let info_length: usize = 512;
-        let mut info_log: Vec<u8> = Vec::with_capacity(info_length - 1);
         gl::GetProgramiv(shader_program_id, gl::LINK_STATUS, &mut status);
         if status == i32::from(gl::TRUE) {
-            true
+            Ok(())
         } else {
-            gl::GetProgramInfoLog(
-                shader_program_id,
-                info_length.try_into().unwrap(),
-                ptr::null_mut(),
-                info_log.as_mut_ptr().cast::<i8>(),
-            );
-            println!(
-                "Shader program link error\n{}",
-                std::str::from_utf8(&info_log).unwrap()
-            );
-            false
+            let log = ShaderProgram::read_program_info_log(shader_program_id);
+            Err(Error::ShaderLink(ShaderDiagnostic::link(&log)))
         }
     }
+
+    unsafe fn read_shader_info_log(shader_id: u32) -> String {
+        let mut length = 0;
+        gl::GetShaderiv(shader_id, gl::INFO_LOG_LENGTH, &mut length);
+        let mut info_log: Vec<u8> = vec![0; usize::try_from(length).unwrap_or(0) + 1];
+        let mut written = 0;
+        gl::GetShaderInfoLog(
+            shader_id,
+            info_log.len().try_into().unwrap_or(i32::MAX),
+            &mut written,
+            info_log.as_mut_ptr().cast::<gl::types::GLchar>(),
+        );
+        info_log.truncate(written.try_into().unwrap_or(0));
+        String::from_utf8_lossy(&info_log).into_owned()
+    }
+
+    unsafe fn read_program_info_log(shader_program_id: u32) -> String {
+        let mut length = 0;
+        gl::GetProgramiv(shader_program_id, gl::INFO_LOG_LENGTH, &mut length);
+        let mut info_log: Vec<u8> = vec![0; usize::try_from(length).unwrap_or(0) + 1];
+        let mut written = 0;
+        gl::GetProgramInfoLog(
+            shader_program_id,
+            info_log.len().try_into().unwrap_or(i32::MAX),
+            &mut written,
+            info_log.as_mut_ptr().cast::<gl::types::GLchar>(),
+        );
+        info_log.truncate(written.try_into().unwrap_or(0));
+        String::from_utf8_lossy(&info_log).into_owned()
+    }
 }
 
-impl Drop for ShaderProgram {
+impl LinkedProgram {
+    pub(crate) fn new() -> Self {
+        LinkedProgram {
+            id: unsafe { gl::CreateProgram() },
+            uniform_locations: HashMap::new(),
+            reflection: ProgramReflection::default(),
+        }
+    }
+
+    pub(crate) fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    /// Compiles the stages, links them and queries the active resources.
+    /// The shader objects are deleted again in any case.
+    pub(crate) fn link(&mut self, sources: &[(ShaderType, &str)]) -> Result<(), Error> {
+        unsafe {
+            let mut shader_ids = Vec::with_capacity(sources.len());
+            for &(stage, src) in sources {
+                match compile_shader(stage, src) {
+                    Ok(id) => shader_ids.push(id),
+                    Err(error) => {
+                        for id in shader_ids {
+                            gl::DeleteShader(id);
+                        }
+                        return Err(error);
+                    }
+                }
+            }
+
+            for &id in &shader_ids {
+                gl::AttachShader(self.id, id);
+            }
+            gl::LinkProgram(self.id);
+            let result = ShaderProgram::check_link_status(self.id);
+            for id in shader_ids {
+                gl::DetachShader(self.id, id);
+                gl::DeleteShader(id);
+            }
+            result?;
+            self.reflection = ProgramReflection::query(self.id);
+        }
+        self.uniform_locations = self.reflection.get_uniform_locations();
+        Ok(())
+    }
+
+    pub(crate) fn activate(&self) {
+        use_program(self.id);
+    }
+
+    pub(crate) fn set_uniform<T: Uniform + ?Sized>(
+        &self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let location = self
+            .get_uniform_location(name)
+            .ok_or_else(|| Error::UniformNotFound(String::from(name)))?;
+        upload_uniform(self.id, location, value);
+        Ok(())
+    }
+
+    /// Uploads the current values of the uniforms of `source` that this
+    /// program declares with the same name and type
+    pub(crate) fn copy_uniform_values(&self, source: &LinkedProgram) {
+        let values: HashMap<String, UniformValue> = source
+            .reflection
+            .get_uniform_elements()
+            .into_iter()
+            .filter_map(|(name, location, glsl_type)| {
+                let value = unsafe { UniformValue::read(source.id, location, glsl_type)? };
+                Some((name, value))
+            })
+            .collect();
+        for (name, location, glsl_type) in self.reflection.get_uniform_elements() {
+            if let Some(value) = values
+                .get(&name)
+                .filter(|value| value.get_type() == glsl_type)
+            {
+                upload_uniform(self.id, location, value);
+            }
+        }
+    }
+
+    pub(crate) fn get_uniform_location(&self, name: &str) -> Option<i32> {
+        self.uniform_locations.get(name).copied()
+    }
+
+    pub(crate) fn get_reflection(&self) -> &ProgramReflection {
+        &self.reflection
+    }
+}
+
+impl Drop for LinkedProgram {
     fn drop(&mut self) {
         unsafe {
-            gl::DeleteProgram(self.shader_program_id);
+            gl::DeleteProgram(self.id);
         }
     }
 }
 
+/// Version of the current context, e.g. `(3, 3)`
+#[must_use]
+pub fn get_context_version() -> (u32, u32) {
+    let mut major = 0;
+    let mut minor = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+    (
+        u32::try_from(major).unwrap_or(0),
+        u32::try_from(minor).unwrap_or(0),
+    )
+}
+
+/// Shader object of one stage, deleted again if it fails to compile
+unsafe fn compile_shader(shader_type: ShaderType, src: &str) -> Result<u32, Error> {
+    let c_str_src = source_to_c_string(shader_type, src)?;
+    let shader = gl::CreateShader(shader_type_as_id(shader_type));
+    gl::ShaderSource(shader, 1, &c_str_src.as_ptr(), ptr::null());
+    gl::CompileShader(shader);
+
+    let mut status = i32::from(gl::FALSE);
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+    if status == i32::from(gl::TRUE) {
+        Ok(shader)
+    } else {
+        let log = ShaderProgram::read_shader_info_log(shader);
+        gl::DeleteShader(shader);
+        Err(Error::ShaderCompilation(ShaderDiagnostic::compilation(
+            shader_type,
+            &log,
+            src,
+        )))
+    }
+}
+
+/// OpenGL reads the source up to the first NUL byte, so one inside it is
+/// reported like a compilation error instead of silently cutting it off
+fn source_to_c_string(shader_type: ShaderType, src: &str) -> Result<CString, Error> {
+    CString::new(src.as_bytes()).map_err(|error| {
+        let log = format!(
+            "ERROR: source contains a NUL byte at byte {}",
+            error.nul_position()
+        );
+        Error::ShaderCompilation(ShaderDiagnostic::compilation(shader_type, &log, src))
+    })
+}
+
+/// Uploads right away if `program_id` is active, otherwise binds it only for
+/// the duration of the upload
+fn upload_uniform<T: Uniform + ?Sized>(program_id: u32, location: i32, value: &T) {
+    let active = get_program();
+    if active == program_id {
+        value.upload(location);
+        return;
+    }
+    unsafe {
+        gl::UseProgram(program_id);
+        value.upload(location);
+        gl::UseProgram(active);
+    }
+}
+
 fn shader_type_as_id(shader_type: ShaderType) -> u32 {
     match shader_type {
         ShaderType::Vertex => gl::VERTEX_SHADER,
+        ShaderType::TessControl => gl::TESS_CONTROL_SHADER,
+        ShaderType::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+        ShaderType::Geometry => gl::GEOMETRY_SHADER,
         ShaderType::Fragment => gl::FRAGMENT_SHADER,
+        ShaderType::Compute => gl::COMPUTE_SHADER,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_support() {
+        assert!(ShaderType::Geometry.check_support((3, 3)).is_ok());
+        assert!(ShaderType::TessControl.check_support((4, 1)).is_ok());
+        assert!(ShaderType::Compute.check_support((4, 6)).is_ok());
+
+        let error = ShaderType::Compute.check_support((3, 3)).unwrap_err();
+        assert!(matches!(error, Error::UnsupportedFeature(_)));
+        assert_eq!(
+            "Not supported by the OpenGL context: Compute shaders need OpenGL 4.3, the context is 3.3",
+            error.to_string()
+        );
+        assert!(ShaderType::TessEvaluation.check_support((3, 3)).is_err());
+    }
+
+    #[test]
+    fn test_source_with_nul_byte() {
+        let error = source_to_c_string(ShaderType::Fragment, "void main() {}\0").unwrap_err();
+        let Error::ShaderCompilation(diagnostic) = error else {
+            panic!("expected a compilation error, got {error}");
+        };
+        assert_eq!(Some(ShaderType::Fragment), diagnostic.stage);
+        assert!(diagnostic.log.contains("NUL byte at byte 14"));
+        assert!(source_to_c_string(ShaderType::Fragment, "void main() {}").is_ok());
     }
 }
@@ -0,0 +1,256 @@
+use crate::color::RGBA;
+use crate::math::matrix::{Mat3, Mat4};
+use crate::math::vector::{Vec2, Vec3, Vec4};
+use crate::shaders::reflection::GlslType;
+
+/// Value that can be uploaded to a uniform of the currently active program.
+///
+/// Implement it for your own types (e.g. a transform) by delegating to one
+/// of the implementations below.
+pub trait Uniform {
+    fn upload(&self, location: i32);
+}
+
+/// Texture unit index bound to a `sampler*` uniform
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sampler(pub u32);
+
+impl Uniform for f32 {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform1f(location, *self);
+        }
+    }
+}
+
+impl Uniform for i32 {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform1i(location, *self);
+        }
+    }
+}
+
+impl Uniform for u32 {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform1ui(location, *self);
+        }
+    }
+}
+
+impl Uniform for bool {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform1i(location, i32::from(*self));
+        }
+    }
+}
+
+impl Uniform for Sampler {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform1i(location, i32::try_from(self.0).unwrap_or(0));
+        }
+    }
+}
+
+impl Uniform for [f32; 2] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform2f(location, self[0], self[1]);
+        }
+    }
+}
+
+impl Uniform for [f32; 3] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform3f(location, self[0], self[1], self[2]);
+        }
+    }
+}
+
+impl Uniform for [f32; 4] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform4f(location, self[0], self[1], self[2], self[3]);
+        }
+    }
+}
+
+impl Uniform for [i32; 2] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform2i(location, self[0], self[1]);
+        }
+    }
+}
+
+impl Uniform for [i32; 3] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform3i(location, self[0], self[1], self[2]);
+        }
+    }
+}
+
+impl Uniform for [i32; 4] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::Uniform4i(location, self[0], self[1], self[2], self[3]);
+        }
+    }
+}
+
+/// Column-major 2x2 matrix
+impl Uniform for [[f32; 2]; 2] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::UniformMatrix2fv(location, 1, gl::FALSE, self.as_ptr().cast::<f32>());
+        }
+    }
+}
+
+/// Column-major 3x3 matrix
+impl Uniform for [[f32; 3]; 3] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::UniformMatrix3fv(location, 1, gl::FALSE, self.as_ptr().cast::<f32>());
+        }
+    }
+}
+
+/// Column-major 4x4 matrix
+impl Uniform for [[f32; 4]; 4] {
+    fn upload(&self, location: i32) {
+        unsafe {
+            gl::UniformMatrix4fv(location, 1, gl::FALSE, self.as_ptr().cast::<f32>());
+        }
+    }
+}
+
+impl Uniform for Vec2 {
+    fn upload(&self, location: i32) {
+        self.to_array().upload(location);
+    }
+}
+
+impl Uniform for Vec3 {
+    fn upload(&self, location: i32) {
+        self.to_array().upload(location);
+    }
+}
+
+impl Uniform for Vec4 {
+    fn upload(&self, location: i32) {
+        self.to_array().upload(location);
+    }
+}
+
+impl Uniform for Mat3 {
+    fn upload(&self, location: i32) {
+        self.cols.upload(location);
+    }
+}
+
+impl Uniform for Mat4 {
+    fn upload(&self, location: i32) {
+        self.cols.upload(location);
+    }
+}
+
+impl Uniform for RGBA {
+    fn upload(&self, location: i32) {
+        self.get_as_normalized_f32().upload(location);
+    }
+}
+
+impl<T: Uniform + ?Sized> Uniform for &T {
+    fn upload(&self, location: i32) {
+        (**self).upload(location);
+    }
+}
+
+/// Value read back from a linked program, used to carry uniforms over to a
+/// rebuilt program. Matrices are column-major.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum UniformValue {
+    Float(GlslType, [f32; 16]),
+    Int(GlslType, [i32; 4]),
+    UInt(GlslType, [u32; 4]),
+}
+
+impl UniformValue {
+    /// `None` for types other than scalars, vectors, matrices and samplers
+    pub(crate) unsafe fn read(program_id: u32, location: i32, glsl_type: GlslType) -> Option<Self> {
+        match glsl_type {
+            GlslType::Float(_) | GlslType::Matrix(..) => {
+                let mut values = [0.0; 16];
+                gl::GetUniformfv(program_id, location, values.as_mut_ptr());
+                Some(UniformValue::Float(glsl_type, values))
+            }
+            GlslType::Int(_) | GlslType::Bool(_) | GlslType::Sampler(_) => {
+                let mut values = [0; 4];
+                gl::GetUniformiv(program_id, location, values.as_mut_ptr());
+                Some(UniformValue::Int(glsl_type, values))
+            }
+            GlslType::UInt(_) => {
+                let mut values = [0; 4];
+                gl::GetUniformuiv(program_id, location, values.as_mut_ptr());
+                Some(UniformValue::UInt(glsl_type, values))
+            }
+            GlslType::Other(_) => None,
+        }
+    }
+
+    pub(crate) fn get_type(&self) -> GlslType {
+        match self {
+            UniformValue::Float(glsl_type, _)
+            | UniformValue::Int(glsl_type, _)
+            | UniformValue::UInt(glsl_type, _) => *glsl_type,
+        }
+    }
+}
+
+impl Uniform for UniformValue {
+    fn upload(&self, location: i32) {
+        let components = self.get_type().get_components();
+        unsafe {
+            match self {
+                UniformValue::Float(GlslType::Matrix(columns, rows), values) => {
+                    let upload_matrix = match (columns, rows) {
+                        (2, 2) => gl::UniformMatrix2fv,
+                        (2, 3) => gl::UniformMatrix2x3fv,
+                        (2, 4) => gl::UniformMatrix2x4fv,
+                        (3, 2) => gl::UniformMatrix3x2fv,
+                        (3, 3) => gl::UniformMatrix3fv,
+                        (3, 4) => gl::UniformMatrix3x4fv,
+                        (4, 2) => gl::UniformMatrix4x2fv,
+                        (4, 3) => gl::UniformMatrix4x3fv,
+                        _ => gl::UniformMatrix4fv,
+                    };
+                    upload_matrix(location, 1, gl::FALSE, values.as_ptr());
+                }
+                UniformValue::Float(_, values) => match components {
+                    1 => gl::Uniform1fv(location, 1, values.as_ptr()),
+                    2 => gl::Uniform2fv(location, 1, values.as_ptr()),
+                    3 => gl::Uniform3fv(location, 1, values.as_ptr()),
+                    _ => gl::Uniform4fv(location, 1, values.as_ptr()),
+                },
+                UniformValue::Int(_, values) => match components {
+                    1 => gl::Uniform1iv(location, 1, values.as_ptr()),
+                    2 => gl::Uniform2iv(location, 1, values.as_ptr()),
+                    3 => gl::Uniform3iv(location, 1, values.as_ptr()),
+                    _ => gl::Uniform4iv(location, 1, values.as_ptr()),
+                },
+                UniformValue::UInt(_, values) => match components {
+                    1 => gl::Uniform1uiv(location, 1, values.as_ptr()),
+                    2 => gl::Uniform2uiv(location, 1, values.as_ptr()),
+                    3 => gl::Uniform3uiv(location, 1, values.as_ptr()),
+                    _ => gl::Uniform4uiv(location, 1, values.as_ptr()),
+                },
+            }
+        }
+    }
+}
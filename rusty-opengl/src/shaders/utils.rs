@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::shaders::registry::with_shader_registry;
 use crate::shaders::shader_program::ShaderProgram;
 use std::env;
 use std::fs::OpenOptions;
@@ -10,15 +12,17 @@ static DELIMETER: char = '\\';
 #[cfg(unix)]
 static DELIMETER: char = '/';
 
-/// # Panics
-#[must_use]
-pub fn create_shader_program(vertex_name: &str, fragment_name: &str) -> ShaderProgram {
-    let path_vertex_src = get_path_to_shaders() + "vertex/" + vertex_name;
-    let path_fragment_src = get_path_to_shaders() + "fragment/" + fragment_name;
-
-    let vertex_src = read_src_from_file(Path::new(&path_vertex_src)).unwrap();
-    let fragment_src = read_src_from_file(Path::new(&path_fragment_src)).unwrap();
-    ShaderProgram::new(&vertex_src, &fragment_src)
+/// Program from the shader registry of the current thread, see
+/// `with_shader_registry`. Does not depend on the working directory.
+///
+/// # Errors
+///
+/// Will return `Err` if any of the shader sources could not be found
+pub fn create_shader_program(
+    vertex_name: &str,
+    fragment_name: &str,
+) -> Result<ShaderProgram, Error> {
+    with_shader_registry(|registry| registry.create_program(vertex_name, fragment_name))
 }
 
 /// # Panics
@@ -31,31 +35,31 @@ pub fn get_current_dir_name() -> String {
 
 /// # Errors
 ///
-/// Will return `Err` if `filename` does not exist or the user does not have
-/// permission to read it.
-/// # Panics
-///
-/// Will panic if file has an invalid format
-pub fn read_src_from_file(path: &Path) -> Result<String, String> {
+/// Will return `Err` if `filename` does not exist, the user does not have
+/// permission to read it or the file is not valid UTF-8.
+pub fn read_src_from_file(path: &Path) -> Result<String, Error> {
     let mut result = String::new();
 
     if path.is_file() {
-        let mut file = OpenOptions::new()
-            .read(true)
-            .open(path.to_str().unwrap_or(""))
-            .unwrap();
-        file.read_to_string(&mut result).unwrap();
+        let mut file = OpenOptions::new().read(true).open(path)?;
+        file.read_to_string(&mut result)?;
         Ok(result)
     } else {
-        Err(format!(
-            "File could not be opened, path: {}",
-            path.to_str().unwrap()
-        ))
+        Err(Error::ShaderSourceNotFound(path.to_path_buf()))
     }
 }
 
-fn get_path_to_shaders() -> String {
-    String::from("rusty-opengl/src/shaders/")
+/// Path in the temporary directory that no other test or test process uses
+#[cfg(test)]
+pub(crate) fn unique_temp_path(name: &str) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    env::temp_dir().join(format!(
+        "rusty_opengl_{}_{}_{name}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
 }
 
 #[cfg(test)]
@@ -67,22 +71,23 @@ mod tests {
     #[test]
     fn test_read_src_from_file_no_file() {
         let result = read_src_from_file(Path::new("/nonExistedPath"));
-        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::ShaderSourceNotFound(_))));
     }
 
     #[test]
     fn test_read_src_from_file_exists() {
         let text = "Hello World file reader";
-        let file_name = "file_reader_test.txt";
+        let file_name = unique_temp_path("file_reader_test.txt");
         let mut file = OpenOptions::new()
             .write(true)
             .create(true)
-            .open(file_name)
+            .truncate(true)
+            .open(&file_name)
             .unwrap();
         let write_result = writeln!(file, "{}", text);
         assert!(write_result.is_ok());
 
-        let result = read_src_from_file(Path::new(file_name)).unwrap();
+        let result = read_src_from_file(&file_name).unwrap();
         assert_eq!(result.trim(), text);
 
         assert!(fs::remove_file(file_name).is_ok());
@@ -0,0 +1,58 @@
+use crate::error::Error;
+use crate::shaders::preprocessor::ShaderDefines;
+use crate::shaders::registry::with_shader_registry;
+use crate::shaders::shader_program::ShaderProgram;
+use std::collections::HashMap;
+
+/// Compiled permutations of a pair of registry shaders, keyed by the defines
+/// they were built with
+pub struct ShaderVariants {
+    vertex_name: String,
+    fragment_name: String,
+    programs: HashMap<ShaderDefines, ShaderProgram>,
+}
+
+impl ShaderVariants {
+    #[must_use]
+    pub fn new(vertex_name: &str, fragment_name: &str) -> Self {
+        ShaderVariants {
+            vertex_name: String::from(vertex_name),
+            fragment_name: String::from(fragment_name),
+            programs: HashMap::new(),
+        }
+    }
+
+    /// `basic.vert` and `basic.frag` with the `VERTEX_COLOR`, `UNIFORM_COLOR`
+    /// and `TEXTURED` flags
+    #[must_use]
+    pub fn basic() -> Self {
+        ShaderVariants::new("basic.vert", "basic.frag")
+    }
+
+    /// Compiles the variant on first use, later calls return the cached one
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the variant could not be preprocessed, compiled
+    /// or linked. Failed variants are not cached.
+    pub fn get(&mut self, defines: &ShaderDefines) -> Result<&ShaderProgram, Error> {
+        if !self.programs.contains_key(defines) {
+            let mut program = with_shader_registry(|registry| {
+                registry.create_variant(&self.vertex_name, &self.fragment_name, defines)
+            })?;
+            program.compile()?;
+            self.programs.insert(defines.clone(), program);
+        }
+        Ok(&self.programs[defines])
+    }
+
+    #[must_use]
+    pub fn get_compiled_count(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Drops the compiled programs, e.g. after the registry sources changed
+    pub fn clear(&mut self) {
+        self.programs.clear();
+    }
+}
@@ -0,0 +1,33 @@
+#version 330 core
+// Variants: VERTEX_COLOR, UNIFORM_COLOR, TEXTURED
+layout (location = 0) in vec3 aPos;
+#ifdef VERTEX_COLOR
+layout (location = 1) in vec4 aColor;
+#endif
+#ifdef TEXTURED
+layout (location = 2) in vec2 aTexCoord;
+out vec2 TexCoord;
+#endif
+
+uniform mat4 model;
+uniform mat4 viewProjection;
+#ifdef UNIFORM_COLOR
+uniform vec4 uniformColor;
+#endif
+
+out vec4 ourColor;
+
+void main()
+{
+    gl_Position = viewProjection * model * vec4(aPos, 1.0);
+    ourColor = vec4(1.0);
+#ifdef VERTEX_COLOR
+    ourColor *= aColor;
+#endif
+#ifdef UNIFORM_COLOR
+    ourColor *= uniformColor;
+#endif
+#ifdef TEXTURED
+    TexCoord = aTexCoord;
+#endif
+}
@@ -1,11 +1,13 @@
 #version 330 core
 layout (location = 0) in vec3 aPos;
 
+uniform mat4 model;
+uniform mat4 viewProjection;
 uniform vec4 circleColor;
 out vec4 ourColor;
 
 void main()
 {
-    gl_Position = vec4(aPos, 1.0);
+    gl_Position = viewProjection * model * vec4(aPos, 1.0);
     ourColor = circleColor;
 }
\ No newline at end of file